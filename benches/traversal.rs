@@ -0,0 +1,22 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use cubeb_coreaudio_samples::{property_reads, traverse_to_node, TraversalOptions};
+
+fn traversal(c: &mut Criterion) {
+    let opt = TraversalOptions::INCLUDE_ALL;
+
+    let reads = property_reads();
+    let node = traverse_to_node(opt);
+    println!(
+        "Full traversal: {} property reads, {} top-level children",
+        property_reads() - reads,
+        node.children().count()
+    );
+
+    let mut group = c.benchmark_group("traversal");
+    group.sample_size(10);
+    group.bench_function("full", |b| b.iter(|| traverse_to_node(opt)));
+    group.finish();
+}
+
+criterion_group!(benches, traversal);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+use cubeb_backend::ffi::{cubeb, cubeb_init};
+use std::{env, ffi::c_int, ptr};
+
+/// Environment variable selecting how the binaries create their cubeb context: "rust" (the
+/// default) for cubeb-coreaudio's `audiounit_rust_init`, or "system" for the generic `cubeb_init`.
+pub const BACKEND_ENV_VAR: &str = "CUBEB_SAMPLES_BACKEND";
+
+pub fn init_context(ctx: &mut *mut cubeb) -> c_int {
+    match env::var(BACKEND_ENV_VAR).as_deref() {
+        Ok("rust") | Err(_) => unsafe {
+            cubeb_coreaudio::audiounit_rust_init(ctx, ptr::null_mut())
+        },
+        Ok("system") => unsafe {
+            cubeb_init(ctx, c"cubeb-coreaudio-samples".as_ptr(), ptr::null())
+        },
+        Ok(other) => panic!("Unknown {} {:?}, expected rust or system", BACKEND_ENV_VAR, other),
+    }
+}
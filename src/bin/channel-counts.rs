@@ -0,0 +1,72 @@
+use coreaudio_sys::AudioDeviceID;
+use cubeb_backend::ffi::*;
+use cubeb_coreaudio_samples::{backend::init_context, channel_count, Scope};
+use std::{
+    ffi::{c_char, CStr},
+    ptr, slice,
+};
+
+extern "C" {
+    fn print_log(msg: *const c_char, ...);
+}
+
+fn cstr_or_empty(s: *const c_char) -> String {
+    if s.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned()
+}
+
+fn main() {
+    assert_eq!(CUBEB_OK, unsafe { cubeb_set_log_callback(CUBEB_LOG_NORMAL, Some(print_log)) });
+
+    let mut ctx: *mut cubeb = ptr::null_mut();
+    assert_eq!(CUBEB_OK, init_context(&mut ctx));
+    assert_ne!(ctx, ptr::null_mut());
+
+    let mut collection = cubeb_device_collection::default();
+    assert_eq!(CUBEB_OK, unsafe {
+        cubeb_enumerate_devices(
+            ctx,
+            CUBEB_DEVICE_TYPE_INPUT | CUBEB_DEVICE_TYPE_OUTPUT,
+            &mut collection,
+        )
+    });
+
+    let devices = if collection.device.is_null() {
+        &[][..]
+    } else {
+        unsafe { slice::from_raw_parts(collection.device, collection.count) }
+    };
+    println!("{:>8} {:<6} {:>6} {:>9}  Name", "DeviceID", "Type", "cubeb", "CoreAudio");
+    let mut mismatches = 0;
+    for info in devices {
+        // cubeb-coreaudio uses the AudioDeviceID as the cubeb devid.
+        let id = info.devid as AudioDeviceID;
+        let (kind, scope) = if info.device_type == CUBEB_DEVICE_TYPE_INPUT {
+            ("Input", Scope::Input)
+        } else {
+            ("Output", Scope::Output)
+        };
+        let coreaudio_channels = channel_count(id, scope);
+        let mismatch = coreaudio_channels != Ok(info.max_channels);
+        if mismatch {
+            mismatches += 1;
+        }
+        println!(
+            "{:>8} {:<6} {:>6} {:>9}  {}{}",
+            id,
+            kind,
+            info.max_channels,
+            coreaudio_channels.map_or_else(|e| format!("Err({})", e), |c| c.to_string()),
+            cstr_or_empty(info.friendly_name),
+            if mismatch { "  <-- MISMATCH" } else { "" }
+        );
+    }
+    println!("{} device(s), {} mismatch(es)", devices.len(), mismatches);
+
+    unsafe { cubeb_device_collection_destroy(ctx, &mut collection) };
+    unsafe { cubeb_destroy(ctx) };
+
+    assert_eq!(CUBEB_OK, unsafe { cubeb_set_log_callback(CUBEB_LOG_DISABLED, None) });
+}
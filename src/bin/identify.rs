@@ -0,0 +1,47 @@
+use clap::Parser;
+use coreaudio_sys::*;
+use cubeb_coreaudio_samples::{device_for_uid, get_string_property, identify_device};
+use std::{thread, time::Duration};
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// The UID of the device to identify.
+    #[clap(long, short)]
+    uid: String,
+    /// For how many seconds the device should identify itself.
+    #[clap(long, short, default_value_t = 5)]
+    seconds: u64,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let id = match device_for_uid(&args.uid) {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("No device with UID {:?} found: {}", args.uid, e);
+            std::process::exit(1);
+        }
+    };
+    let name = get_string_property(id, kAudioObjectPropertyName);
+
+    match identify_device(id, true) {
+        Ok(()) => {}
+        Err(e) if e == kAudioHardwareUnknownPropertyError as OSStatus => {
+            eprintln!("Device {} ({:?}) does not support identification", id, name);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to identify device {} ({:?}): {}", id, name, e);
+            std::process::exit(1);
+        }
+    }
+    println!("Identifying device {} ({:?}) for {} seconds", id, name, args.seconds);
+
+    thread::sleep(Duration::from_secs(args.seconds));
+
+    if let Err(e) = identify_device(id, false) {
+        eprintln!("Failed to stop identifying device {}: {}", id, e);
+        std::process::exit(1);
+    }
+}
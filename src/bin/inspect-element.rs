@@ -0,0 +1,112 @@
+use clap::Parser;
+use coreaudio_sys::*;
+use cubeb_coreaudio_samples::{
+    get_property_element, get_string_property, get_string_property_element, has_property_element,
+    Scope,
+};
+use std::fmt::Debug;
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// The AudioObjectID of the device to inspect.
+    #[clap(long, short)]
+    object: AudioObjectID,
+    /// The element, i.e. channel, to inspect. 0 is the main element.
+    #[clap(long, short)]
+    element: u32,
+    /// The scope to inspect in: global, input, output or playthrough.
+    #[clap(long, short, default_value = "output")]
+    scope: Scope,
+}
+
+fn print_element_property<T: Default + Debug>(
+    obj: AudioObjectID,
+    name: &str,
+    selector: u32,
+    scope: Scope,
+    element: u32,
+) {
+    if !has_property_element(obj, selector, scope.into(), element) {
+        return;
+    }
+    match get_property_element::<T>(obj, selector, scope.into(), element) {
+        Ok(value) => println!("  {}: {:?}", name, value),
+        Err(e) => println!("  {}: Err({})", name, e),
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    let (obj, scope, element) = (args.object, args.scope, args.element);
+
+    println!(
+        "AudioObjectID {} ({:?}), {} scope, element {}:",
+        obj,
+        get_string_property(obj, kAudioObjectPropertyName),
+        scope,
+        element
+    );
+    for (name, selector) in [
+        ("ElementName", kAudioObjectPropertyElementName),
+        ("ElementCategoryName", kAudioObjectPropertyElementCategoryName),
+        ("ElementNumberName", kAudioObjectPropertyElementNumberName),
+    ] {
+        if has_property_element(obj, selector, scope.into(), element) {
+            let value = get_string_property_element(obj, selector, scope.into(), element);
+            println!("  {}: {:?}", name, value);
+        }
+    }
+    print_element_property::<f32>(
+        obj,
+        "VolumeScalar",
+        kAudioDevicePropertyVolumeScalar,
+        scope,
+        element,
+    );
+    print_element_property::<f32>(
+        obj,
+        "VolumeDecibels",
+        kAudioDevicePropertyVolumeDecibels,
+        scope,
+        element,
+    );
+    print_element_property::<AudioValueRange>(
+        obj,
+        "VolumeRangeDecibels",
+        kAudioDevicePropertyVolumeRangeDecibels,
+        scope,
+        element,
+    );
+    print_element_property::<f32>(obj, "StereoPan", kAudioDevicePropertyStereoPan, scope, element);
+    print_element_property::<u32>(obj, "Mute", kAudioDevicePropertyMute, scope, element);
+    print_element_property::<u32>(obj, "Solo", kAudioDevicePropertySolo, scope, element);
+    print_element_property::<u32>(
+        obj,
+        "PhantomPower",
+        kAudioDevicePropertyPhantomPower,
+        scope,
+        element,
+    );
+    print_element_property::<u32>(
+        obj,
+        "PhaseInvert",
+        kAudioDevicePropertyPhaseInvert,
+        scope,
+        element,
+    );
+    print_element_property::<u32>(obj, "ClipLight", kAudioDevicePropertyClipLight, scope, element);
+    print_element_property::<u32>(
+        obj,
+        "DataSource",
+        kAudioDevicePropertyDataSource,
+        scope,
+        element,
+    );
+    print_element_property::<u32>(
+        obj,
+        "ChannelNominalLineLevel",
+        kAudioDevicePropertyChannelNominalLineLevel,
+        scope,
+        element,
+    );
+}
@@ -0,0 +1,48 @@
+use clap::{Parser, ValueEnum};
+use cubeb_coreaudio_samples::{get_process_input_mute, set_process_input_mute};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Action {
+    On,
+    Off,
+    Toggle,
+}
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// Whether to mute, unmute or toggle the microphone mute.
+    #[clap(long, value_enum)]
+    mic_mute: Action,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let muted = match args.mic_mute {
+        Action::On => true,
+        Action::Off => false,
+        Action::Toggle => match get_process_input_mute() {
+            Ok(muted) => !muted,
+            Err(e) => {
+                eprintln!("Failed to read the microphone mute: {}", e);
+                std::process::exit(1);
+            }
+        },
+    };
+    if let Err(e) = set_process_input_mute(muted) {
+        eprintln!("Failed to set the microphone mute to {}: {}", muted, e);
+        std::process::exit(1);
+    }
+
+    match get_process_input_mute() {
+        Ok(current) if current == muted => println!("Microphone mute is now {}", current),
+        Ok(current) => {
+            eprintln!("Microphone mute is {}, expected {}", current, muted);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to read back the microphone mute: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
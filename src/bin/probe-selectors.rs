@@ -0,0 +1,63 @@
+use clap::Parser;
+use coreaudio_sys::*;
+use cubeb_coreaudio_samples::{
+    fourcc_to_string, get_property, probe_selectors,
+    selectors::{known_selectors_for_classes, ALL},
+    Scope,
+};
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// The AudioObjectID to probe. Defaults to the default output device.
+    #[clap(long, short)]
+    object: Option<AudioObjectID>,
+    /// The scope to probe in: global, input, output or playthrough.
+    #[clap(long, short, default_value = "global")]
+    scope: Scope,
+    /// Probe every known selector, not only those of the object's class and the classes it
+    /// derives from.
+    #[clap(long, action)]
+    all: bool,
+    /// Also list the selectors the object does not respond to.
+    #[clap(long, short = 'm', action)]
+    show_missing: bool,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let obj = args.object.unwrap_or_else(|| {
+        get_property::<AudioObjectID>(
+            kAudioObjectSystemObject,
+            kAudioHardwarePropertyDefaultOutputDevice,
+        )
+        .expect("Default output device")
+    });
+
+    let known: Vec<(&str, u32)> = if args.all {
+        ALL.iter().flat_map(|group| group.iter().copied()).collect()
+    } else {
+        let class =
+            |selector| get_property::<AudioClassID>(obj, selector).unwrap_or(kAudioObjectClassID);
+        known_selectors_for_classes(
+            class(kAudioObjectPropertyBaseClass),
+            class(kAudioObjectPropertyClass),
+        )
+    };
+    let selectors: Vec<u32> = known.iter().map(|&(_, s)| s).collect();
+    let results = probe_selectors(obj, args.scope, &selectors);
+    println!(
+        "AudioObjectID {} responds to {} of {} known selectors in {} scope:",
+        obj,
+        results.iter().filter(|&&(_, exists)| exists).count(),
+        results.len(),
+        args.scope
+    );
+    for (&(name, selector), (_, exists)) in known.iter().zip(results) {
+        if exists {
+            println!("  {} {}", fourcc_to_string(selector), name);
+        } else if args.show_missing {
+            println!("  {} {} (missing)", fourcc_to_string(selector), name);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use clap::Parser;
+use coreaudio_sys::*;
+use cubeb_coreaudio_samples::{
+    get_list_property, get_property, get_string_property, process_object_for_pid,
+};
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// The pid of the process to look up.
+    #[clap(long, short)]
+    pid: pid_t,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let id = match process_object_for_pid(args.pid) {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("No process object for pid {} found: {}", args.pid, e);
+            std::process::exit(1);
+        }
+    };
+    println!("AudioObjectID: {}", id);
+    println!("BundleID: {:?}", get_string_property(id, kAudioProcessPropertyBundleID));
+    println!("Devices: {:?}", get_list_property::<AudioObjectID>(id, kAudioProcessPropertyDevices));
+    println!(
+        "IsRunning: {:?}",
+        get_property::<u32>(id, kAudioProcessPropertyIsRunning).map(|p| p != 0)
+    );
+}
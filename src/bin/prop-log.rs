@@ -0,0 +1,152 @@
+use clap::Parser;
+use coreaudio_sys::*;
+use cubeb_coreaudio_samples::{
+    audio_object_add_property_listener, audio_object_remove_property_listener, fourcc_to_string,
+    get_property, scope_to_str, Scope,
+};
+use std::{
+    ffi::c_void,
+    io, slice,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+const DEFAULT_SELECTORS: [AudioObjectPropertySelector; 4] = [
+    kAudioHardwarePropertyDevices,
+    kAudioHardwarePropertyDefaultInputDevice,
+    kAudioHardwarePropertyDefaultOutputDevice,
+    kAudioHardwarePropertyDefaultSystemOutputDevice,
+];
+
+/// Parses a selector given either as a four character code, like "dev#", or as a number.
+fn parse_selector(s: &str) -> Result<AudioObjectPropertySelector, String> {
+    match <[u8; 4]>::try_from(s.as_bytes()) {
+        Ok(bytes) if s.parse::<u32>().is_err() => Ok(u32::from_be_bytes(bytes)),
+        _ => s
+            .parse::<u32>()
+            .map_err(|_| format!("{:?} is neither a four character code nor a number", s)),
+    }
+}
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// The AudioObjectID to listen on. Defaults to the system object.
+    #[clap(long, short, default_value_t = kAudioObjectSystemObject)]
+    object: AudioObjectID,
+    /// The scope to listen in: global, input, output or playthrough.
+    #[clap(long, short, default_value = "global")]
+    scope: Scope,
+    /// A selector to listen to, as a four character code like "dev#" or as a number. May be
+    /// repeated. Defaults to the device list and default device selectors. E.g. "gone" on a device
+    /// logs when it goes active or idle.
+    #[clap(long = "selector", value_parser = parse_selector)]
+    selectors: Vec<AudioObjectPropertySelector>,
+}
+
+/// Shared with the listener.
+struct State {
+    start: Instant,
+    /// Notifications of kAudioHardwarePropertyDevices. A device list that keeps changing points to
+    /// a misbehaving driver.
+    device_list_changes: AtomicUsize,
+}
+
+impl State {
+    /// Device list changes per minute since logging started.
+    fn device_list_change_rate(&self) -> f64 {
+        let minutes = self.start.elapsed().as_secs_f64() / 60.0;
+        self.device_list_changes.load(Ordering::Relaxed) as f64 / minutes
+    }
+}
+
+extern "C" fn log_listener(
+    id: AudioObjectID,
+    num_addresses: u32,
+    addresses: *const AudioObjectPropertyAddress,
+    data: *mut c_void,
+) -> OSStatus {
+    let state = unsafe { &*(data as *const State) };
+    let elapsed = state.start.elapsed().as_secs_f64();
+    let addresses = unsafe { slice::from_raw_parts(addresses, num_addresses as usize) };
+    for address in addresses {
+        println!(
+            "{:10.3}s object {} selector {} scope {} element {}",
+            elapsed,
+            id,
+            fourcc_to_string(address.mSelector),
+            scope_to_str(address.mScope),
+            address.mElement
+        );
+        if address.mSelector == kAudioHardwarePropertyDevices {
+            let changes = state.device_list_changes.fetch_add(1, Ordering::Relaxed) + 1;
+            println!(
+                "{:10.3}s device list changed {} times ({:.1}/min)",
+                elapsed,
+                changes,
+                state.device_list_change_rate()
+            );
+        }
+        if address.mSelector == kAudioDevicePropertyDeviceIsRunningSomewhere {
+            match get_property::<u32>(id, kAudioDevicePropertyDeviceIsRunningSomewhere) {
+                Ok(0) => println!("{:10.3}s device {} is now idle", elapsed, id),
+                Ok(_) => println!("{:10.3}s device {} is now active", elapsed, id),
+                Err(e) => println!("{:10.3}s device {} running state: Err({})", elapsed, id, e),
+            }
+        }
+    }
+    0
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let selectors = if args.selectors.is_empty() {
+        DEFAULT_SELECTORS.to_vec()
+    } else {
+        args.selectors
+    };
+    let addresses: Vec<AudioObjectPropertyAddress> = selectors
+        .iter()
+        .map(|&selector| AudioObjectPropertyAddress {
+            mSelector: selector,
+            mScope: args.scope.into(),
+            mElement: kAudioObjectPropertyElementMaster,
+        })
+        .collect();
+
+    let state = Box::into_raw(Box::new(State {
+        start: Instant::now(),
+        device_list_changes: AtomicUsize::new(0),
+    }));
+    for address in &addresses {
+        let status =
+            audio_object_add_property_listener(args.object, address, Some(log_listener), state);
+        if status != 0 {
+            eprintln!(
+                "Failed to listen to {} on object {}: {}",
+                fourcc_to_string(address.mSelector),
+                args.object,
+                status
+            );
+        }
+    }
+
+    println!("Logging property notifications. <ENTER> to quit.");
+    let _ = io::stdin().read_line(&mut String::new());
+
+    for address in &addresses {
+        audio_object_remove_property_listener(args.object, address, Some(log_listener), state);
+    }
+    let state = unsafe { Box::from_raw(state) };
+    if addresses
+        .iter()
+        .any(|a| a.mSelector == kAudioHardwarePropertyDevices)
+    {
+        println!(
+            "Device list changed {} times in {:.1}s ({:.1}/min)",
+            state.device_list_changes.load(Ordering::Relaxed),
+            state.start.elapsed().as_secs_f64(),
+            state.device_list_change_rate()
+        );
+    }
+}
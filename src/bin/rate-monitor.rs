@@ -0,0 +1,78 @@
+use clap::Parser;
+use coreaudio_sys::*;
+use cubeb_coreaudio_samples::{device_for_uid, get_property, get_string_property};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// The UID of the device to monitor.
+    #[clap(long, short)]
+    uid: String,
+    /// For how many seconds to monitor.
+    #[clap(long, short, default_value_t = 10)]
+    duration: u64,
+    /// How many milliseconds to wait between reads.
+    #[clap(long, short, default_value_t = 100)]
+    interval: u64,
+    /// Flag reads where the actual rate deviates more than this many ppm from the nominal rate.
+    #[clap(long, short, default_value_t = 100.0)]
+    threshold: f64,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let id = match device_for_uid(&args.uid) {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("No device with UID {:?} found: {}", args.uid, e);
+            std::process::exit(1);
+        }
+    };
+    println!(
+        "Monitoring AudioObjectID {} ({:?}) for {} seconds",
+        id,
+        get_string_property(id, kAudioObjectPropertyName),
+        args.duration
+    );
+
+    let start = Instant::now();
+    let end = start + Duration::from_secs(args.duration);
+    let mut flagged = 0;
+    while Instant::now() < end {
+        let nominal = get_property::<f64>(id, kAudioDevicePropertyNominalSampleRate);
+        let actual = get_property::<f64>(id, kAudioDevicePropertyActualSampleRate);
+        let elapsed = start.elapsed().as_secs_f64();
+        match (nominal, actual) {
+            (Ok(nominal), Ok(actual)) => {
+                let delta = actual - nominal;
+                let ppm = if nominal > 0.0 {
+                    delta / nominal * 1e6
+                } else {
+                    0.0
+                };
+                let drifting = ppm.abs() > args.threshold;
+                if drifting {
+                    flagged += 1;
+                }
+                println!(
+                    "{:8.3}s nominal {} actual {:.3} delta {:+.3} Hz ({:+.1} ppm){}",
+                    elapsed,
+                    nominal,
+                    actual,
+                    delta,
+                    ppm,
+                    if drifting { "  <-- DRIFT" } else { "" }
+                );
+            }
+            (nominal, actual) => {
+                println!("{:8.3}s nominal {:?} actual {:?}", elapsed, nominal, actual)
+            }
+        }
+        thread::sleep(Duration::from_millis(args.interval));
+    }
+    println!("{} read(s) drifted more than {} ppm", flagged, args.threshold);
+}
@@ -0,0 +1,44 @@
+use clap::Parser;
+use coreaudio_sys::*;
+use cubeb_coreaudio_samples::{device_for_uid, get_string_property, set_object_name};
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// The UID of the device to rename, e.g. of an aggregate device.
+    #[clap(long, short)]
+    uid: String,
+    /// The new name.
+    #[clap(long, short)]
+    name: String,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let id = match device_for_uid(&args.uid) {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("No device with UID {:?} found: {}", args.uid, e);
+            std::process::exit(1);
+        }
+    };
+    let old_name = get_string_property(id, kAudioObjectPropertyName);
+
+    match set_object_name(id, &args.name) {
+        Ok(()) => {}
+        Err(e) if e == kAudioHardwareUnsupportedOperationError as OSStatus => {
+            eprintln!("Device {} ({:?}) does not allow renaming", id, old_name);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to rename device {} ({:?}): {}", id, old_name, e);
+            std::process::exit(1);
+        }
+    }
+    println!(
+        "Renamed device {} from {:?} to {:?}",
+        id,
+        old_name,
+        get_string_property(id, kAudioObjectPropertyName)
+    );
+}
@@ -0,0 +1,11 @@
+use cubeb_coreaudio_samples::round_trip_latency_estimate;
+
+fn main() {
+    match round_trip_latency_estimate() {
+        Ok(ms) => println!("Estimated round-trip latency: {:.2} ms", ms),
+        Err(e) => {
+            eprintln!("Failed to estimate the round-trip latency: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+/// The sample binaries in this crate, with what each is for. Keep this up to date when adding a
+/// binary.
+const SAMPLES: &[(&str, &str)] = &[
+    ("channel-counts", "Compare cubeb's and CoreAudio's channel counts for each device"),
+    ("enumeration", "Print cubeb's device enumeration"),
+    ("identify", "Make a device identify itself, e.g. by blinking an LED"),
+    ("inspect-element", "Read the element-scoped properties of an object"),
+    ("mic-mute", "Mute, unmute or toggle the process input mute"),
+    ("probe-selectors", "List which known property selectors an object responds to"),
+    ("process-for-pid", "Resolve a pid to its audio process object"),
+    ("prop-log", "Log property listener notifications as they arrive"),
+    ("rate-monitor", "Monitor a device's actual sample rate against its nominal rate"),
+    ("rename", "Rename a device that allows it, like an aggregate device"),
+    ("round-trip-latency", "Estimate the round-trip latency of the default input and output"),
+    ("samples", "List the sample binaries (this one)"),
+    ("set-default", "Make a device the default input or output device"),
+    ("set-stream-format", "Request a stream format and report what CoreAudio applied"),
+    ("stream-churn", "Repeatedly open and close streams, traversing in between"),
+    ("traversal", "Print the CoreAudio object tree with its properties"),
+    ("vpio-echoes", "Record through a VoiceProcessingIO unit to check echo cancellation"),
+    ("vpio-enumeration", "Print cubeb's device enumeration while a VoiceProcessingIO unit runs"),
+    ("vpio-firefox-echoes", "Like vpio-echoes, but primes a stream first like Firefox does"),
+    ("who-is-recording", "List the processes currently capturing audio input"),
+];
+
+fn main() {
+    let width = SAMPLES
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (name, description) in SAMPLES {
+        println!("{:width$}  {}", name, description, width = width);
+    }
+    println!("\nRun one with: cargo run --bin <name> -- --help");
+}
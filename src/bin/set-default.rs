@@ -0,0 +1,63 @@
+use clap::{Parser, ValueEnum};
+use coreaudio_sys::*;
+use cubeb_coreaudio_samples::{
+    device_for_uid, get_property, get_string_property, set_default_input_device,
+    set_default_output_device,
+};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Direction {
+    Input,
+    Output,
+}
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// The UID of the device to make default.
+    #[clap(long, short)]
+    uid: String,
+    /// Which default device to set.
+    #[clap(long, short, value_enum)]
+    direction: Direction,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let id = match device_for_uid(&args.uid) {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("No device with UID {:?} found: {}", args.uid, e);
+            std::process::exit(1);
+        }
+    };
+    let name = get_string_property(id, kAudioObjectPropertyName);
+    println!("Resolved UID {:?} to AudioObjectID {} ({:?})", args.uid, id, name);
+
+    let (result, selector) = match args.direction {
+        Direction::Input => {
+            (set_default_input_device(id), kAudioHardwarePropertyDefaultInputDevice)
+        }
+        Direction::Output => {
+            (set_default_output_device(id), kAudioHardwarePropertyDefaultOutputDevice)
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to set default {:?} device: {}", args.direction, e);
+        std::process::exit(1);
+    }
+
+    match get_property::<AudioObjectID>(kAudioObjectSystemObject, selector) {
+        Ok(current) if current == id => {
+            println!("Default {:?} device is now {}", args.direction, current)
+        }
+        Ok(current) => {
+            eprintln!("Default {:?} device is {}, expected {}", args.direction, current, id);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to read back default {:?} device: {}", args.direction, e);
+            std::process::exit(1);
+        }
+    }
+}
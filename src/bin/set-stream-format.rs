@@ -0,0 +1,70 @@
+use clap::Parser;
+use coreaudio_sys::*;
+use cubeb_coreaudio_samples::{
+    asbd_summary, get_property, linear_pcm_format, set_stream_physical_format,
+    set_stream_virtual_format,
+};
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// The AudioStreamID of the stream to configure.
+    #[clap(long, short)]
+    stream: AudioStreamID,
+    /// The sample rate to request.
+    #[clap(long, short)]
+    rate: f64,
+    /// The bit depth to request. 32 means float, anything else signed integer.
+    #[clap(long, short, default_value_t = 32)]
+    bits: u32,
+    /// Set the physical format instead of the virtual format.
+    #[clap(long, short, action)]
+    physical: bool,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let (selector, kind) = if args.physical {
+        (kAudioStreamPropertyPhysicalFormat, "physical")
+    } else {
+        (kAudioStreamPropertyVirtualFormat, "virtual")
+    };
+    let current = match get_property::<AudioStreamBasicDescription>(args.stream, selector) {
+        Ok(current) => current,
+        Err(e) => {
+            eprintln!("Failed to read the {} format of stream {}: {}", kind, args.stream, e);
+            std::process::exit(1);
+        }
+    };
+    println!("Current {} format: {}", kind, asbd_summary(current));
+
+    let requested = linear_pcm_format(args.rate, current.mChannelsPerFrame, args.bits);
+    println!("Requesting {} format: {}", kind, asbd_summary(requested));
+    let result = if args.physical {
+        set_stream_physical_format(args.stream, &requested)
+    } else {
+        set_stream_virtual_format(args.stream, &requested)
+    };
+    if let Err(e) = result {
+        eprintln!("WARNING: CoreAudio rejected the {} format: {}", kind, e);
+        std::process::exit(1);
+    }
+
+    match get_property::<AudioStreamBasicDescription>(args.stream, selector) {
+        Ok(actual) => {
+            println!("Resulting {} format: {}", kind, asbd_summary(actual));
+            if actual.mSampleRate != requested.mSampleRate
+                || actual.mBitsPerChannel != requested.mBitsPerChannel
+                || actual.mFormatFlags & kAudioFormatFlagIsFloat
+                    != requested.mFormatFlags & kAudioFormatFlagIsFloat
+            {
+                eprintln!("WARNING: CoreAudio coerced the requested {} format", kind);
+                std::process::exit(1);
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to read back the {} format: {}", kind, e);
+            std::process::exit(1);
+        }
+    }
+}
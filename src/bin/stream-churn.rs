@@ -0,0 +1,125 @@
+extern crate cubeb_coreaudio_samples;
+use clap::Parser;
+use cubeb_backend::ffi::*;
+use cubeb_coreaudio_samples::{backend::init_context, traverse_with_options, TraversalOptions};
+use std::{
+    ffi::{c_char, c_void},
+    mem, ptr,
+};
+
+extern "C" {
+    fn print_log(msg: *const c_char, ...);
+}
+
+pub extern "C" fn noop_data_callback(
+    stream: *mut cubeb_stream,
+    _user_ptr: *mut c_void,
+    _input_buffer: *const c_void,
+    output_buffer: *mut c_void,
+    nframes: i64,
+) -> i64 {
+    assert!(!stream.is_null());
+
+    // Feed silence data to output buffer
+    if !output_buffer.is_null() {
+        const CHANNELS: usize = 1;
+        let samples = nframes as usize * CHANNELS as usize;
+        const SAMPLE_SIZE: usize = mem::size_of::<f32>();
+        unsafe {
+            ptr::write_bytes(output_buffer, 0, samples * SAMPLE_SIZE);
+        }
+    }
+
+    nframes
+}
+
+pub extern "C" fn noop_state_callback(
+    _stream: *mut cubeb_stream,
+    _user_ptr: *mut c_void,
+    _state: u32,
+) {
+}
+
+#[derive(Parser, Debug)]
+struct Args {
+    /// How many times to init, start, stop and destroy a stream.
+    #[clap(long, short, default_value_t = 100)]
+    iterations: usize,
+    /// Traverse the object tree, with streams, every this many iterations. 0 never traverses.
+    #[clap(long, short, default_value_t = 10)]
+    traverse_every: usize,
+    /// Open a duplex stream with a VoiceProcessingIO unit instead of an output-only stream.
+    #[clap(long, short, action)]
+    vpio: bool,
+}
+
+/// Fails if the context no longer answers queries after a stream cycle.
+fn assert_context_valid(ctx: *mut cubeb, iteration: usize) {
+    let mut max_channels = 0;
+    let rv = unsafe { cubeb_get_max_channel_count(ctx, &mut max_channels) };
+    assert_eq!(CUBEB_OK, rv, "Context stopped answering after iteration {}", iteration);
+    assert!(max_channels > 0, "Context reports no output channels after iteration {}", iteration);
+}
+
+fn main() {
+    let args = Args::parse();
+
+    assert_eq!(CUBEB_OK, unsafe { cubeb_set_log_callback(CUBEB_LOG_NORMAL, Some(print_log)) });
+
+    let mut ctx: *mut cubeb = ptr::null_mut();
+    assert_eq!(CUBEB_OK, init_context(&mut ctx));
+    assert_ne!(ctx, ptr::null_mut());
+
+    let prefs = if args.vpio {
+        CUBEB_STREAM_PREF_VOICE
+    } else {
+        CUBEB_STREAM_PREF_NONE
+    };
+    let params = || cubeb_stream_params {
+        channels: 1,
+        format: CUBEB_SAMPLE_FLOAT32NE,
+        rate: 48000,
+        layout: CUBEB_LAYOUT_MONO,
+        prefs,
+    };
+    let mut input_params = params();
+    let mut output_params = params();
+    let input_params: *mut cubeb_stream_params = if args.vpio {
+        &mut input_params
+    } else {
+        ptr::null_mut()
+    };
+
+    for i in 1..=args.iterations {
+        let mut stream: *mut cubeb_stream = ptr::null_mut();
+        assert_eq!(CUBEB_OK, unsafe {
+            cubeb_stream_init(
+                ctx,
+                &mut stream,
+                c"stream-churn".as_ptr(),  // Stream name.
+                ptr::null_mut(),           // Default input device.
+                input_params,              // Input params, if any.
+                ptr::null_mut(),           // Default output device.
+                &mut output_params,        // Output params.
+                512,                       // Latency in frames.
+                Some(noop_data_callback),  // Data callback.
+                Some(noop_state_callback), // State Callback.
+                ptr::null_mut(),           // User pointer.
+            )
+        });
+        assert_eq!(CUBEB_OK, unsafe { cubeb_stream_start(stream) });
+        assert_eq!(CUBEB_OK, unsafe { cubeb_stream_stop(stream) });
+        unsafe { cubeb_stream_destroy(stream) };
+        assert_context_valid(ctx, i);
+
+        if args.traverse_every > 0 && i % args.traverse_every == 0 {
+            println!("Iteration {}:", i);
+            traverse_with_options(TraversalOptions::INCLUDE_STREAMS);
+        }
+    }
+    println!("{} stream cycles completed", args.iterations);
+
+    unsafe { cubeb_destroy(ctx) };
+
+    assert_eq!(CUBEB_OK, unsafe { cubeb_set_log_callback(CUBEB_LOG_DISABLED, None) });
+}
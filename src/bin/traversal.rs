@@ -1,9 +1,20 @@
 use clap::Parser;
+use coreaudio_sys::*;
 use cubeb_backend::ffi::*;
-use cubeb_coreaudio_samples::{traverse_with_options, TraversalOptions};
+use cubeb_coreaudio_samples::{
+    audio_object_add_property_listener, audio_object_remove_property_listener,
+    backend::init_context, fourcc_to_string, get_property, get_property_scoped, install_panic_hook,
+    probe_selectors, set_wrap_width, sink::print_changes, summarize, system_header, system_summary,
+    traversal_errors, traverse_default_devices, traverse_pid, traverse_to_node,
+    traverse_with_options, Scope, TraversalOptions,
+};
 use std::{
+    collections::VecDeque,
     ffi::{c_char, c_void},
-    io, mem, ptr,
+    io, mem, ptr, slice,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
 };
 
 extern "C" {
@@ -40,11 +51,161 @@ pub extern "C" fn noop_state_callback(
     println!("Stream {:p}: STATE is now {}", stream, state);
 }
 
+// Bypass, AGC and ducking are properties of the VPIO AudioUnit itself, which cubeb owns. These
+// are the voice processing related properties a device may expose.
+const VOICE_PROCESSING_SELECTORS: [(&str, AudioObjectPropertySelector); 4] = [
+    ("VoiceActivityDetectionEnable", kAudioDevicePropertyVoiceActivityDetectionEnable),
+    ("VoiceActivityDetectionState", kAudioDevicePropertyVoiceActivityDetectionState),
+    ("ProcessMute", kAudioDevicePropertyProcessMute),
+    ("Mute", kAudioDevicePropertyMute),
+];
+
+fn print_voice_processing_properties() {
+    let device = match get_property::<AudioObjectID>(
+        kAudioObjectSystemObject,
+        kAudioHardwarePropertyDefaultInputDevice,
+    ) {
+        Ok(device) => device,
+        Err(e) => {
+            println!("Failed to get the default input device: {}", e);
+            return;
+        }
+    };
+    println!("Voice processing properties of default input device {}:", device);
+    let selectors = VOICE_PROCESSING_SELECTORS.map(|(_, selector)| selector);
+    for scope in [Scope::Global, Scope::Input] {
+        let results = probe_selectors(device, scope, &selectors);
+        for ((name, selector), (_, exists)) in VOICE_PROCESSING_SELECTORS.iter().zip(results) {
+            if exists {
+                let value = get_property_scoped::<u32>(device, *selector, scope.into());
+                println!("  {} {}: {:?}", scope, name, value);
+            }
+        }
+    }
+}
+
+const WATCHED_SELECTORS: [AudioObjectPropertySelector; 4] = [
+    kAudioHardwarePropertyDevices,
+    kAudioHardwarePropertyDefaultInputDevice,
+    kAudioHardwarePropertyDefaultOutputDevice,
+    kAudioHardwarePropertyDefaultSystemOutputDevice,
+];
+
+extern "C" fn watch_listener(
+    _id: AudioObjectID,
+    num_addresses: u32,
+    addresses: *const AudioObjectPropertyAddress,
+    data: *mut c_void,
+) -> OSStatus {
+    let tx = unsafe { &*(data as *const mpsc::Sender<Option<AudioObjectPropertySelector>>) };
+    let addresses = unsafe { slice::from_raw_parts(addresses, num_addresses as usize) };
+    for address in addresses {
+        let _ = tx.send(Some(address.mSelector));
+    }
+    0
+}
+
+fn describe_change(selector: AudioObjectPropertySelector) -> String {
+    let default_device =
+        |name: &str| match get_property::<AudioObjectID>(kAudioObjectSystemObject, selector) {
+            Ok(id) => format!("{} changed to {}", name, id),
+            Err(e) => format!("{} changed, but reading it failed: {}", name, e),
+        };
+    #[allow(non_upper_case_globals)]
+    match selector {
+        kAudioHardwarePropertyDevices => "Device list changed".to_string(),
+        kAudioHardwarePropertyDefaultInputDevice => default_device("Default input device"),
+        kAudioHardwarePropertyDefaultOutputDevice => default_device("Default output device"),
+        kAudioHardwarePropertyDefaultSystemOutputDevice => {
+            default_device("Default system output device (alerts and sound effects)")
+        }
+        s => format!("Property {} changed", fourcc_to_string(s)),
+    }
+}
+
+fn watch(opt: TraversalOptions) {
+    // None asks to quit.
+    let (tx, rx) = mpsc::channel::<Option<AudioObjectPropertySelector>>();
+    let quit = tx.clone();
+    let tx = Box::into_raw(Box::new(tx));
+    let addresses = WATCHED_SELECTORS.map(|selector| AudioObjectPropertyAddress {
+        mSelector: selector,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    });
+    for address in &addresses {
+        assert_eq!(
+            0,
+            audio_object_add_property_listener(
+                kAudioObjectSystemObject,
+                address,
+                Some(watch_listener),
+                tx,
+            )
+        );
+    }
+
+    thread::spawn(move || {
+        for line in io::stdin().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if ["q", "quit", "exit"].contains(&line.trim()) {
+                let _ = quit.send(None);
+                break;
+            }
+        }
+    });
+
+    println!("Watching for device changes. q/quit/exit to quit.");
+    traverse_with_options(opt);
+    while let Ok(Some(selector)) = rx.recv() {
+        println!("{}", describe_change(selector));
+        traverse_with_options(opt);
+    }
+
+    for address in &addresses {
+        audio_object_remove_property_listener(
+            kAudioObjectSystemObject,
+            address,
+            Some(watch_listener),
+            tx,
+        );
+    }
+    drop(unsafe { Box::from_raw(tx) });
+}
+
 #[derive(Parser, Debug)]
 struct Args {
     /// Wait indefinitely, re-traversing on <Enter>.
     #[clap(long, short, action)]
     wait: bool,
+    /// Watch until q/quit/exit is entered, re-traversing whenever the device list or a default
+    /// device changes.
+    #[clap(long, action, conflicts_with = "wait")]
+    watch: bool,
+    /// Re-traverse every this many milliseconds, timing each pass and flagging passes that take
+    /// more than 3x the median of the last 100. In a full traversal, what changed since the
+    /// previous pass is marked with "* ".
+    #[clap(long, conflicts_with_all = ["wait", "watch"])]
+    interval: Option<u64>,
+    /// Traverse this many times, then exit, instead of waiting for input. With --interval, the
+    /// passes are that far apart.
+    #[clap(
+        long,
+        conflicts_with_all = ["wait", "watch"],
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    times: Option<usize>,
+    /// Only print a one-line summary of how many devices, streams, etc. there are.
+    #[clap(long, action, conflicts_with_all = ["wait", "watch", "interval", "times"])]
+    counts: bool,
+    /// Only traverse the default input and output devices, with their streams and controls.
+    #[clap(long, action, conflicts_with_all = ["counts", "watch"])]
+    defaults_only: bool,
+    /// Only traverse the process object of this pid, and the devices it uses with their streams.
+    #[clap(long, conflicts_with_all = ["counts", "watch", "defaults_only"])]
+    pid: Option<pid_t>,
     /// Include everything when traversing.
     #[clap(long, short = 'a', action)]
     include_all: bool,
@@ -63,32 +224,76 @@ struct Args {
     /// Include device channels when traversing. Lists of channel descriptions can be quite verbose.
     #[clap(long, short = 'n', action)]
     include_channels: bool,
+    /// Print included channel layouts on one line, like "7.1 (L R C LFE Ls Rs Rls Rrs)", where
+    /// all channel labels are known.
+    #[clap(long, action)]
+    compact_channels: bool,
     /// Include controls when traversing. They are often plenty and therefore quite verbose.
     #[clap(long, short = 'c', action)]
     include_controls: bool,
     /// Include plugins when traversing.
     #[clap(long, short = 'l', action)]
     include_plugins: bool,
-    /// Include processes when traversing.
+    /// Include processes when traversing, and which processes use each device.
     #[clap(long, short = 'p', action)]
     include_processes: bool,
+    /// Include the system's process taps when traversing. Implied by --include-processes.
+    #[clap(long, action)]
+    include_taps: bool,
+    /// Include the custom properties third-party drivers declare when traversing.
+    #[clap(long, action)]
+    include_custom_properties: bool,
+    /// With --include-custom-properties, also show the raw bytes of custom properties whose type
+    /// is not a CFString or CFPropertyList.
+    #[clap(long, action)]
+    hexdump: bool,
+    /// Leave out string properties that are empty. --debug shows them anyway.
+    #[clap(long, action)]
+    hide_empty: bool,
+    /// Annotate each object with the resolved chain of owners up to the system object.
+    #[clap(long, short = 'o', action)]
+    show_owners: bool,
+    /// Sort devices and other owned objects by UID, then name, so captures are diff-stable.
+    #[clap(long, action)]
+    sorted: bool,
+    /// Traverse the system object's children, like devices, on several threads. The output is the
+    /// same as without this.
+    #[clap(long, action)]
+    parallel: bool,
+    /// Skip output-scoped properties when traversing.
+    #[clap(long, action, conflicts_with = "output_only")]
+    input_only: bool,
+    /// Skip input-scoped properties when traversing.
+    #[clap(long, action)]
+    output_only: bool,
     /// Debug mode. Show all errors for getters that failed.
     #[clap(long, short = 'd', action)]
     debug: bool,
     /// Set up a VoiceProcessingIO unit before traversing, to see what streams and channels it adds.
     #[clap(long, short = 'v', action)]
     use_vpio: bool,
+    /// Let a panic while traversing an object abort the run, instead of skipping that object.
+    #[clap(long, action)]
+    propagate_panics: bool,
+    /// Don't print the macOS version and hardware model before the output.
+    #[clap(long, action)]
+    no_header: bool,
+    /// Exit with code 1 if any property read failed during traversal.
+    #[clap(long, action)]
+    fail_on_error: bool,
+    /// Truncate the lines of verbose multi-line properties, like formats, to this many columns.
+    #[clap(long)]
+    wrap: Option<usize>,
 }
 
 fn main() {
     let args = Args::parse();
+    install_panic_hook();
 
     assert_eq!(CUBEB_OK, unsafe { cubeb_set_log_callback(CUBEB_LOG_NORMAL, Some(print_log)) });
 
     let mut ctx: *mut cubeb = ptr::null_mut();
-    assert_eq!(CUBEB_OK, unsafe {
-        cubeb_coreaudio::audiounit_rust_init(&mut ctx, ptr::null_mut())
-    });
+    assert_eq!(CUBEB_OK, init_context(&mut ctx));
     assert_ne!(ctx, ptr::null_mut());
 
     let mut stream: *mut cubeb_stream = ptr::null_mut();
@@ -116,6 +321,7 @@ fn main() {
             )
         });
         assert_eq!(CUBEB_OK, unsafe { cubeb_stream_start(stream) });
+        print_voice_processing_properties();
     }
 
     let mut opt = TraversalOptions::empty();
@@ -134,6 +340,9 @@ fn main() {
     if args.include_channels {
         opt.insert(TraversalOptions::INCLUDE_CHANNELS);
     }
+    if args.compact_channels {
+        opt.insert(TraversalOptions::COMPACT_CHANNELS);
+    }
     if args.include_controls {
         opt.insert(TraversalOptions::INCLUDE_CONTROLS);
     }
@@ -143,15 +352,111 @@ fn main() {
     if args.include_processes {
         opt.insert(TraversalOptions::INCLUDE_PROCESSES);
     }
+    if args.include_taps {
+        opt.insert(TraversalOptions::INCLUDE_TAPS);
+    }
+    if args.include_custom_properties {
+        opt.insert(TraversalOptions::INCLUDE_CUSTOM_PROPERTIES);
+    }
+    if args.hexdump {
+        opt.insert(TraversalOptions::HEXDUMP);
+    }
+    if args.hide_empty {
+        opt.insert(TraversalOptions::HIDE_EMPTY);
+    }
+    if args.show_owners {
+        opt.insert(TraversalOptions::SHOW_OWNERS);
+    }
+    if args.input_only {
+        opt.insert(TraversalOptions::INPUT_ONLY);
+    }
+    if args.output_only {
+        opt.insert(TraversalOptions::OUTPUT_ONLY);
+    }
+    if args.sorted {
+        opt.insert(TraversalOptions::SORTED);
+    }
+    if args.parallel {
+        opt.insert(TraversalOptions::PARALLEL);
+    }
     if args.include_all {
-        opt = TraversalOptions::all();
-        opt.remove(TraversalOptions::DEBUG);
+        opt.insert(TraversalOptions::INCLUDE_ALL);
     }
     if args.debug {
         opt.insert(TraversalOptions::DEBUG);
     }
+    if args.propagate_panics {
+        opt.insert(TraversalOptions::PROPAGATE_PANICS);
+    }
+    set_wrap_width(args.wrap);
+
+    if !args.no_header {
+        println!("{}", system_header());
+    }
+    if !args.counts {
+        system_summary(opt);
+    }
+    let traverse = |opt| {
+        if let Some(pid) = args.pid {
+            if let Err(e) = traverse_pid(pid, opt) {
+                eprintln!("No process object for pid {} found: {}", pid, e);
+            }
+        } else if args.defaults_only {
+            traverse_default_devices(opt);
+        } else {
+            traverse_with_options(opt);
+        }
+    };
 
-    if args.wait {
+    if args.counts {
+        println!("{}", summarize());
+    } else if args.watch {
+        watch(opt);
+    } else if let Some(interval) = args.interval {
+        // Keep memory bounded however long this runs.
+        const WINDOW: usize = 100;
+        let mut durations = VecDeque::with_capacity(WINDOW);
+        let mut passes = 0;
+        let mut previous = None;
+        loop {
+            let start = Instant::now();
+            if args.pid.is_some() || args.defaults_only {
+                traverse(opt);
+            } else {
+                let node = traverse_to_node(opt);
+                print_changes(&node, previous.as_ref());
+                previous = Some(node);
+            }
+            let elapsed = start.elapsed();
+            passes += 1;
+            if durations.len() == WINDOW {
+                durations.pop_front();
+            }
+            durations.push_back(elapsed);
+            let mut sorted: Vec<Duration> = durations.iter().copied().collect();
+            sorted.sort();
+            let median = sorted[sorted.len() / 2];
+            println!(
+                "Pass {} took {:?} (min {:?}, median {:?}, max {:?})",
+                passes,
+                elapsed,
+                sorted[0],
+                median,
+                sorted[sorted.len() - 1]
+            );
+            if elapsed > median * 3 {
+                println!("WARNING: Pass {} took more than 3x the median", passes);
+            }
+            if args.times.is_some_and(|times| passes >= times) {
+                break;
+            }
+            thread::sleep(Duration::from_millis(interval));
+        }
+    } else if let Some(times) = args.times {
+        for _ in 0..times {
+            traverse(opt);
+        }
+    } else if args.wait {
         loop {
             println!("Waiting... <ENTER> to traverse. q/quit/exit to quit.");
             let mut command = String::new();
@@ -160,10 +465,10 @@ fn main() {
             if ["q", "quit", "exit"].contains(&command.as_str()) {
                 break;
             }
-            traverse_with_options(opt);
+            traverse(opt);
         }
     } else {
-        traverse_with_options(opt);
+        traverse(opt);
     }
 
     if !stream.is_null() {
@@ -173,4 +478,10 @@ fn main() {
     unsafe { cubeb_destroy(ctx) };
 
     assert_eq!(CUBEB_OK, unsafe { cubeb_set_log_callback(CUBEB_LOG_DISABLED, None) });
+
+    let errors = traversal_errors();
+    if args.fail_on_error && errors > 0 {
+        eprintln!("{} property read(s) failed", errors);
+        std::process::exit(1);
+    }
 }
@@ -1,5 +1,6 @@
 extern crate cubeb_coreaudio_samples;
 use cubeb_backend::ffi::*;
+use cubeb_coreaudio_samples::backend::init_context;
 use std::{
     ffi::{c_char, c_void},
     mem, ptr,
@@ -43,9 +44,7 @@ fn main() {
     assert_eq!(CUBEB_OK, unsafe { cubeb_set_log_callback(CUBEB_LOG_NORMAL, Some(print_log)) });
 
     let mut ctx: *mut cubeb = ptr::null_mut();
-    assert_eq!(CUBEB_OK, unsafe {
-        cubeb_coreaudio::audiounit_rust_init(&mut ctx, ptr::null_mut())
-    });
+    assert_eq!(CUBEB_OK, init_context(&mut ctx));
     assert_ne!(ctx, ptr::null_mut());
 
     let mut stream: *mut cubeb_stream = ptr::null_mut();
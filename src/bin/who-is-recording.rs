@@ -0,0 +1,57 @@
+use coreaudio_sys::*;
+use cubeb_coreaudio_samples::{get_list_property, get_property, get_string_property};
+use std::ffi::{c_int, c_void};
+
+extern "C" {
+    // From libproc.h.
+    fn proc_name(pid: c_int, buffer: *mut c_void, buffersize: u32) -> c_int;
+}
+
+/// The executable name of `pid`, like "zoom.us", if it can be read.
+fn executable_name(pid: pid_t) -> Option<String> {
+    let mut buffer = [0u8; 256];
+    let len = unsafe { proc_name(pid, buffer.as_mut_ptr() as *mut c_void, buffer.len() as u32) };
+    (len > 0).then(|| String::from_utf8_lossy(&buffer[..len as usize]).into_owned())
+}
+
+fn main() {
+    let processes = match get_list_property::<AudioObjectID>(
+        kAudioObjectSystemObject,
+        kAudioHardwarePropertyProcessObjectList,
+    ) {
+        Ok(processes) => processes,
+        Err(e) => {
+            eprintln!("Failed to read the process object list: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut recording = 0;
+    for process in processes {
+        let running_input =
+            get_property::<u32>(process, kAudioProcessPropertyIsRunningInput).is_ok_and(|p| p != 0);
+        if !running_input {
+            continue;
+        }
+        recording += 1;
+        let pid = get_property::<pid_t>(process, kAudioProcessPropertyPID);
+        let bundle_id = get_string_property(process, kAudioProcessPropertyBundleID);
+        println!(
+            "AudioObjectID {}: pid {}, {}",
+            process,
+            pid.map_or_else(
+                |e| format!("Err({})", e),
+                |p| match executable_name(p) {
+                    Some(name) => format!("{} ({})", p, name),
+                    None => p.to_string(),
+                }
+            ),
+            match bundle_id {
+                Ok(id) if !id.is_empty() => id,
+                Ok(_) => "(no bundle ID)".to_string(),
+                Err(e) => format!("Err({})", e),
+            }
+        );
+    }
+    println!("{} process(es) recording", recording);
+}
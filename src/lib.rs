@@ -1,13 +1,43 @@
-use std::ffi::CString;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::{c_char, CStr, CString};
 use std::fmt;
 use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
 use coreaudio_sys::*;
-use debug_tree::{add_branch, add_leaf, default_tree};
+use debug_tree::default_tree;
 
 use std::os::raw::c_void;
 use std::ptr;
 
+pub mod backend;
+pub mod selectors;
+pub mod sink;
+pub mod wav;
+
+use sink::{
+    emit, sink_branch, with_sink, AudioObjectNode, NodeEntry, NodeSink, PropertySink, RecordingSink,
+};
+
+/// Like debug_tree's `add_branch!`, but through the current `PropertySink`. The branch ends at the
+/// end of the enclosing scope.
+macro_rules! add_branch {
+    ($($arg: tt)*) => {
+        let _branch = sink_branch(&format!($($arg)*));
+    };
+}
+
+/// Like debug_tree's `add_leaf!`, but through the current `PropertySink`.
+macro_rules! add_leaf {
+    ($($arg: tt)*) => {{
+        let text = format!($($arg)*);
+        emit(|sink| sink.leaf(&text))
+    }};
+}
+
 #[derive(Debug)]
 struct StringRef(CFStringRef);
 
@@ -23,10 +53,7 @@ impl StringRef {
     }
 
     fn to_cstring(&self) -> CString {
-        unsafe {
-            // Assume that bytes doesn't contain `0` in the middle.
-            CString::from_vec_unchecked(utf8_from_cfstringref(self.0))
-        }
+        cstring_lossy(utf8_from_cfstringref(self.0))
     }
 
     fn into_cstring(self) -> CString {
@@ -38,6 +65,22 @@ impl StringRef {
     }
 }
 
+/// Converts `bytes` into a CString, replacing any interior NUL with U+FFFD so that strings from
+/// buggy drivers are neither truncated nor invalid.
+fn cstring_lossy(bytes: Vec<u8>) -> CString {
+    CString::new(bytes).unwrap_or_else(|e| {
+        let mut replaced = Vec::new();
+        for b in e.into_vec() {
+            if b == 0 {
+                replaced.extend_from_slice(char::REPLACEMENT_CHARACTER.to_string().as_bytes());
+            } else {
+                replaced.push(b);
+            }
+        }
+        CString::new(replaced).expect("all NULs were replaced")
+    })
+}
+
 fn utf8_from_cfstringref(string_ref: CFStringRef) -> Vec<u8> {
     use std::ptr;
 
@@ -106,12 +149,21 @@ pub fn audio_object_has_property(id: AudioObjectID, address: &AudioObjectPropert
     unsafe { AudioObjectHasProperty(id, address) != 0 }
 }
 
+static PROPERTY_READS: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns how many times `AudioObjectGetPropertyData` has been called through this crate, across
+/// all threads. Take the difference around a traversal to get its cost in property reads.
+pub fn property_reads() -> usize {
+    PROPERTY_READS.load(Ordering::Relaxed)
+}
+
 pub fn audio_object_get_property_data<T>(
     id: AudioObjectID,
     address: &AudioObjectPropertyAddress,
     size: *mut usize,
     data: *mut T,
 ) -> OSStatus {
+    PROPERTY_READS.fetch_add(1, Ordering::Relaxed);
     unsafe {
         AudioObjectGetPropertyData(
             id,
@@ -132,6 +184,7 @@ pub fn audio_object_get_property_data_with_qualifier<T, Q>(
     size: *mut usize,
     data: *mut T,
 ) -> OSStatus {
+    PROPERTY_READS.fetch_add(1, Ordering::Relaxed);
     unsafe {
         AudioObjectGetPropertyData(
             id,
@@ -170,38 +223,241 @@ pub fn audio_object_get_property_data_size_with_qualifier<Q>(
     }
 }
 
-pub fn has_property_scoped(obj: AudioObjectID, selector: u32, scope: u32) -> bool {
+pub fn audio_object_set_property_data<T>(
+    id: AudioObjectID,
+    address: &AudioObjectPropertyAddress,
+    size: usize,
+    data: *const T,
+) -> OSStatus {
+    unsafe {
+        AudioObjectSetPropertyData(
+            id,
+            address,
+            0,
+            ptr::null(),
+            size as UInt32,
+            data as *const c_void,
+        )
+    }
+}
+
+pub fn audio_object_add_property_listener<T>(
+    id: AudioObjectID,
+    address: &AudioObjectPropertyAddress,
+    listener: AudioObjectPropertyListenerProc,
+    data: *mut T,
+) -> OSStatus {
+    unsafe { AudioObjectAddPropertyListener(id, address, listener, data as *mut c_void) }
+}
+
+pub fn audio_object_remove_property_listener<T>(
+    id: AudioObjectID,
+    address: &AudioObjectPropertyAddress,
+    listener: AudioObjectPropertyListenerProc,
+    data: *mut T,
+) -> OSStatus {
+    unsafe { AudioObjectRemovePropertyListener(id, address, listener, data as *mut c_void) }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Scope {
+    Global,
+    Input,
+    Output,
+    PlayThrough,
+}
+
+impl From<Scope> for AudioObjectPropertyScope {
+    fn from(scope: Scope) -> Self {
+        match scope {
+            Scope::Global => kAudioObjectPropertyScopeGlobal,
+            Scope::Input => kAudioObjectPropertyScopeInput,
+            Scope::Output => kAudioObjectPropertyScopeOutput,
+            Scope::PlayThrough => kAudioObjectPropertyScopePlayThrough,
+        }
+    }
+}
+
+pub fn scope_to_str(scope: u32) -> &'static str {
+    #[allow(non_upper_case_globals, non_snake_case)]
+    match scope {
+        kAudioObjectPropertyScopeGlobal => "Global",
+        kAudioObjectPropertyScopeInput => "Input",
+        kAudioObjectPropertyScopeOutput => "Output",
+        kAudioObjectPropertyScopePlayThrough => "PlayThrough",
+        kAudioObjectPropertyScopeWildcard => "Wildcard",
+        _ => "Unexpected Scope",
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", scope_to_str((*self).into()))
+    }
+}
+
+impl std::str::FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "global" => Ok(Scope::Global),
+            "input" => Ok(Scope::Input),
+            "output" => Ok(Scope::Output),
+            "playthrough" => Ok(Scope::PlayThrough),
+            s => Err(format!("Unknown scope {:?}", s)),
+        }
+    }
+}
+
+pub fn fourcc_to_string(code: u32) -> String {
+    let bytes = code.to_be_bytes();
+    if bytes.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+        format!("'{}'", String::from_utf8_lossy(&bytes))
+    } else {
+        format!("{:#010X}", code)
+    }
+}
+
+pub fn has_property_element(obj: AudioObjectID, selector: u32, scope: u32, element: u32) -> bool {
     let address = AudioObjectPropertyAddress {
         mSelector: selector,
         mScope: scope,
-        mElement: kAudioObjectPropertyElementMaster,
+        mElement: element,
     };
     audio_object_has_property(obj, &address)
 }
 
-pub fn get_property_scoped<T: Default>(
+pub fn has_property_scoped(obj: AudioObjectID, selector: u32, scope: u32) -> bool {
+    has_property_element(obj, selector, scope, kAudioObjectPropertyElementMaster)
+}
+
+/// Probes `obj` for each selector in `selectors`, returning whether it has the property in `scope`.
+pub fn probe_selectors(obj: AudioObjectID, scope: Scope, selectors: &[u32]) -> Vec<(u32, bool)> {
+    selectors
+        .iter()
+        .map(|&s| (s, has_property_scoped(obj, s, scope.into())))
+        .collect()
+}
+
+/// With the `checked_reads` feature, getters fail any read where CoreAudio reports a different
+/// size than the type being read into, and log the property address.
+#[cfg(feature = "checked_reads")]
+fn check_read_size(
+    address: &AudioObjectPropertyAddress,
+    expected: usize,
+    reported: usize,
+) -> Result<(), OSStatus> {
+    if expected == reported {
+        return Ok(());
+    }
+    eprintln!(
+        "Size mismatch reading {}: expected {} bytes, CoreAudio reported {}",
+        address_to_string(address.mSelector, address.mScope, address.mElement),
+        expected,
+        reported
+    );
+    Err(kAudioHardwareBadPropertySizeError as OSStatus)
+}
+
+/// Fails with `kAudioHardwareBadPropertySizeError` when CoreAudio reports fewer bytes than a `T`
+/// holds, as reading one would hand out a `T` that is only partially filled in.
+fn check_property_size<T>(reported: usize) -> Result<(), OSStatus> {
+    if reported < mem::size_of::<T>() {
+        return Err(kAudioHardwareBadPropertySizeError as OSStatus);
+    }
+    Ok(())
+}
+
+/// Reads a fixed-size property into a `T`, after checking its reported size. Properties without a
+/// qualifier pass a zero `qualifier_size` and a null `qualifier`.
+fn read_property<T: Default, Q>(
     obj: AudioObjectID,
-    selector: u32,
-    scope: u32,
+    address: &AudioObjectPropertyAddress,
+    qualifier_size: usize,
+    qualifier: *const Q,
 ) -> Result<T, OSStatus> {
-    let address = AudioObjectPropertyAddress {
-        mSelector: selector,
-        mScope: scope,
-        mElement: kAudioObjectPropertyElementMaster,
-    };
+    let mut size = 0;
+    let status = audio_object_get_property_data_size_with_qualifier(
+        obj,
+        address,
+        qualifier_size,
+        qualifier,
+        &mut size,
+    );
+    if status != 0 {
+        return Err(status);
+    }
+    check_property_size::<T>(size)?;
+    #[cfg(feature = "checked_reads")]
+    check_read_size(address, mem::size_of::<T>(), size)?;
     let mut value: T = T::default();
     let mut size = mem::size_of_val(&value);
-    let status = audio_object_get_property_data(obj, &address, &mut size, &mut value);
+    let status = audio_object_get_property_data_with_qualifier(
+        obj,
+        address,
+        qualifier_size,
+        qualifier,
+        &mut size,
+        &mut value,
+    );
     match status {
         0 => Ok(value),
         e => Err(e),
     }
 }
 
+pub fn get_property_element<T: Default>(
+    obj: AudioObjectID,
+    selector: u32,
+    scope: u32,
+    element: u32,
+) -> Result<T, OSStatus> {
+    let address = AudioObjectPropertyAddress {
+        mSelector: selector,
+        mScope: scope,
+        mElement: element,
+    };
+    read_property(obj, &address, 0, ptr::null::<()>())
+}
+
+pub fn get_property_scoped<T: Default>(
+    obj: AudioObjectID,
+    selector: u32,
+    scope: u32,
+) -> Result<T, OSStatus> {
+    get_property_element(obj, selector, scope, kAudioObjectPropertyElementMaster)
+}
+
 pub fn get_property<T: Default>(obj: AudioObjectID, selector: u32) -> Result<T, OSStatus> {
     get_property_scoped(obj, selector, kAudioObjectPropertyScopeGlobal)
 }
 
+/// Reads a property whose value depends on a qualifier, like a selector control item ID.
+pub fn get_property_with_qualifier<T: Default, Q>(
+    obj: AudioObjectID,
+    selector: u32,
+    qualifier: &Q,
+) -> Result<T, OSStatus> {
+    let address = AudioObjectPropertyAddress {
+        mSelector: selector,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    read_property(obj, &address, mem::size_of_val(qualifier), qualifier)
+}
+
+pub fn get_string_property_with_qualifier<Q>(
+    obj: AudioObjectID,
+    selector: u32,
+    qualifier: &Q,
+) -> Result<String, OSStatus> {
+    match get_property_with_qualifier::<usize, Q>(obj, selector, qualifier)? {
+        0 => Err(kAudioHardwareUnspecifiedError as OSStatus),
+        str => Ok(StringRef::new(str as CFStringRef).into_string()),
+    }
+}
+
 pub fn get_list_property_scoped<T: Clone + Default>(
     obj: AudioObjectID,
     selector: u32,
@@ -217,6 +473,8 @@ pub fn get_list_property_scoped<T: Clone + Default>(
     if status != 0 {
         return Err(status);
     }
+    #[cfg(feature = "checked_reads")]
+    check_read_size(&address, size / mem::size_of::<T>() * mem::size_of::<T>(), size)?;
     let mut objects: Vec<T> = vec![T::default(); size / mem::size_of::<T>()];
     let status = audio_object_get_property_data(obj, &address, &mut size, objects.as_mut_ptr());
     match status {
@@ -225,6 +483,20 @@ pub fn get_list_property_scoped<T: Clone + Default>(
     }
 }
 
+/// Returns how many elements of type `T` a list property holds, without reading the list.
+pub fn get_list_property_len<T>(obj: AudioObjectID, selector: u32) -> Result<usize, OSStatus> {
+    let address = AudioObjectPropertyAddress {
+        mSelector: selector,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    let mut size = 0;
+    match audio_object_get_property_data_size(obj, &address, &mut size) {
+        0 => Ok(size / mem::size_of::<T>()),
+        e => Err(e),
+    }
+}
+
 pub fn get_list_property<T: Clone + Default>(
     obj: AudioObjectID,
     selector: u32,
@@ -232,21 +504,322 @@ pub fn get_list_property<T: Clone + Default>(
     get_list_property_scoped(obj, selector, kAudioObjectPropertyScopeGlobal)
 }
 
+pub fn get_string_property_element(
+    obj: AudioObjectID,
+    selector: u32,
+    scope: u32,
+    element: u32,
+) -> Result<String, OSStatus> {
+    let address = AudioObjectPropertyAddress {
+        mSelector: selector,
+        mScope: scope,
+        mElement: element,
+    };
+    // CFStringRef has no Default, so read the pointer as a usize.
+    let str = read_property::<usize, ()>(obj, &address, 0, ptr::null())?;
+    Ok(StringRef::new(str as CFStringRef).into_string())
+}
+
 pub fn get_string_property(obj: AudioObjectID, selector: u32) -> Result<String, OSStatus> {
+    get_string_property_element(
+        obj,
+        selector,
+        kAudioObjectPropertyScopeGlobal,
+        kAudioObjectPropertyElementMaster,
+    )
+}
+
+/// Reads a property whose value is a CFArray of CFStrings, like
+/// `kAudioAggregateDevicePropertyFullSubDeviceList`, into Strings.
+pub fn get_cfstring_array_property(
+    obj: AudioObjectID,
+    selector: u32,
+    scope: u32,
+) -> Result<Vec<String>, OSStatus> {
+    get_property_scoped::<usize>(obj, selector, scope).map(cfarray_strings)
+}
+
+pub fn set_property_scoped<T>(
+    obj: AudioObjectID,
+    selector: u32,
+    scope: u32,
+    value: &T,
+) -> Result<(), OSStatus> {
     let address = AudioObjectPropertyAddress {
         mSelector: selector,
-        mScope: kAudioObjectPropertyScopeGlobal,
+        mScope: scope,
         mElement: kAudioObjectPropertyElementMaster,
     };
-    let mut str: CFStringRef = ptr::null();
-    let mut size = mem::size_of_val(&str);
-    let status = audio_object_get_property_data(obj, &address, &mut size, &mut str);
+    let status = audio_object_set_property_data(obj, &address, mem::size_of::<T>(), value);
     match status {
-        0 => Ok(StringRef::new(str).into_string()),
+        0 => Ok(()),
         e => Err(e),
     }
 }
 
+pub fn set_property<T>(obj: AudioObjectID, selector: u32, value: &T) -> Result<(), OSStatus> {
+    set_property_scoped(obj, selector, kAudioObjectPropertyScopeGlobal, value)
+}
+
+fn cfstring_from_str(string: &str) -> StringRef {
+    let cfstr = unsafe {
+        CFStringCreateWithBytes(
+            kCFAllocatorDefault,
+            string.as_ptr(),
+            string.len() as CFIndex,
+            kCFStringEncodingUTF8,
+            false as Boolean,
+        )
+    };
+    StringRef::new(cfstr)
+}
+
+/// Translates `uid` into an AudioObjectID through one of the system object's TranslateUIDTo*
+/// properties.
+fn translate_uid(
+    selector: AudioObjectPropertySelector,
+    uid: &str,
+) -> Result<AudioObjectID, OSStatus> {
+    let address = AudioObjectPropertyAddress {
+        mSelector: selector,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    let uid = cfstring_from_str(uid);
+    let qualifier = uid.get_raw();
+    read_property(kAudioObjectSystemObject, &address, mem::size_of_val(&qualifier), &qualifier)
+}
+
+/// Translates a device UID into the AudioDeviceID of the device currently carrying it.
+pub fn device_for_uid(uid: &str) -> Result<AudioDeviceID, OSStatus> {
+    #[allow(non_upper_case_globals)]
+    match translate_uid(kAudioHardwarePropertyTranslateUIDToDevice, uid)? {
+        kAudioObjectUnknown => Err(kAudioHardwareBadDeviceError as OSStatus),
+        id => Ok(id),
+    }
+}
+
+/// Translates a clock device UID into the AudioObjectID of the clock device.
+pub fn clock_device_for_uid(uid: &str) -> Result<AudioObjectID, OSStatus> {
+    #[allow(non_upper_case_globals)]
+    match translate_uid(kAudioHardwarePropertyTranslateUIDToClockDevice, uid)? {
+        kAudioObjectUnknown => Err(kAudioHardwareBadObjectError as OSStatus),
+        id => Ok(id),
+    }
+}
+
+/// Translates a tap UID into the AudioObjectID of the tap.
+pub fn tap_for_uid(uid: &str) -> Result<AudioObjectID, OSStatus> {
+    #[allow(non_upper_case_globals)]
+    match translate_uid(kAudioHardwarePropertyTranslateUIDToTap, uid)? {
+        kAudioObjectUnknown => Err(kAudioHardwareBadObjectError as OSStatus),
+        id => Ok(id),
+    }
+}
+
+/// Translates a pid into the AudioObjectID of the process object the HAL keeps for it.
+pub fn process_object_for_pid(pid: pid_t) -> Result<AudioObjectID, OSStatus> {
+    let address = AudioObjectPropertyAddress {
+        mSelector: kAudioHardwarePropertyTranslatePIDToProcessObject,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    let id: AudioObjectID =
+        read_property(kAudioObjectSystemObject, &address, mem::size_of_val(&pid), &pid)?;
+    #[allow(non_upper_case_globals)]
+    match id {
+        kAudioObjectUnknown => Err(kAudioHardwareBadObjectError as OSStatus),
+        id => Ok(id),
+    }
+}
+
+pub fn set_default_input_device(id: AudioDeviceID) -> Result<(), OSStatus> {
+    set_property(kAudioObjectSystemObject, kAudioHardwarePropertyDefaultInputDevice, &id)
+}
+
+pub fn set_default_output_device(id: AudioDeviceID) -> Result<(), OSStatus> {
+    set_property(kAudioObjectSystemObject, kAudioHardwarePropertyDefaultOutputDevice, &id)
+}
+
+pub fn get_process_input_mute() -> Result<bool, OSStatus> {
+    get_property::<u32>(kAudioObjectSystemObject, kAudioHardwarePropertyProcessInputMute)
+        .map(|p| p != 0)
+}
+
+pub fn set_process_input_mute(muted: bool) -> Result<(), OSStatus> {
+    set_property(kAudioObjectSystemObject, kAudioHardwarePropertyProcessInputMute, &(muted as u32))
+}
+
+/// Describes packed, interleaved, native-endian linear PCM. 32-bit samples are float, others are
+/// signed integer.
+pub fn linear_pcm_format(rate: f64, channels: u32, bits: u32) -> AudioStreamBasicDescription {
+    let flags = if bits == 32 {
+        kAudioFormatFlagIsFloat
+    } else {
+        kAudioFormatFlagIsSignedInteger
+    };
+    let bytes_per_frame = bits.div_ceil(8) * channels;
+    AudioStreamBasicDescription {
+        mSampleRate: rate,
+        mFormatID: kAudioFormatLinearPCM,
+        mFormatFlags: flags | kAudioFormatFlagIsPacked,
+        mBytesPerPacket: bytes_per_frame,
+        mFramesPerPacket: 1,
+        mBytesPerFrame: bytes_per_frame,
+        mChannelsPerFrame: channels,
+        mBitsPerChannel: bits,
+        mReserved: 0,
+    }
+}
+
+pub fn set_stream_virtual_format(
+    stream: AudioStreamID,
+    asbd: &AudioStreamBasicDescription,
+) -> Result<(), OSStatus> {
+    set_property(stream, kAudioStreamPropertyVirtualFormat, asbd)
+}
+
+pub fn set_stream_physical_format(
+    stream: AudioStreamID,
+    asbd: &AudioStreamBasicDescription,
+) -> Result<(), OSStatus> {
+    set_property(stream, kAudioStreamPropertyPhysicalFormat, asbd)
+}
+
+/// Renames `obj`, e.g. an aggregate device. Fails with `kAudioHardwareUnsupportedOperationError`
+/// if `obj` does not allow renaming.
+pub fn set_object_name(obj: AudioObjectID, name: &str) -> Result<(), OSStatus> {
+    let address = AudioObjectPropertyAddress {
+        mSelector: kAudioObjectPropertyName,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMaster,
+    };
+    let mut settable: Boolean = 0;
+    match unsafe { AudioObjectIsPropertySettable(obj, &address, &mut settable) } {
+        0 if settable != 0 => {}
+        0 => return Err(kAudioHardwareUnsupportedOperationError as OSStatus),
+        e => return Err(e),
+    }
+    let name = cfstring_from_str(name);
+    set_property(obj, kAudioObjectPropertyName, &name.get_raw())
+}
+
+/// Turns identification, e.g. a blinking LED, on or off for the device. Fails with
+/// `kAudioHardwareUnknownPropertyError` if the device does not support identification.
+pub fn identify_device(obj: AudioObjectID, on: bool) -> Result<(), OSStatus> {
+    if !has_property_scoped(obj, kAudioObjectPropertyIdentify, kAudioObjectPropertyScopeGlobal) {
+        return Err(kAudioHardwareUnknownPropertyError as OSStatus);
+    }
+    set_property(obj, kAudioObjectPropertyIdentify, &(on as u32))
+}
+
+/// Parses the channel count of each buffer out of the raw bytes of an `AudioBufferList`. Buffers
+/// that `data` is too short to hold are left out.
+fn parse_buffer_list(data: &[u8]) -> Vec<u32> {
+    let abl_len = mem::size_of::<AudioBufferList>();
+    let ab_len = mem::size_of::<AudioBuffer>();
+    let abl_base_len = abl_len - ab_len;
+    if data.len() < abl_base_len {
+        return Vec::new();
+    }
+    // Only read mNumberBuffers, as the buffer list may be shorter than an AudioBufferList.
+    let declared = unsafe { ptr::read_unaligned(data.as_ptr() as *const UInt32) } as usize;
+    let num_buffers = declared.min((data.len() - abl_base_len) / ab_len);
+    (0..num_buffers)
+        .map(|i| unsafe {
+            ptr::read_unaligned(
+                data.as_ptr().wrapping_add(abl_base_len + i * ab_len) as *const AudioBuffer
+            )
+        })
+        .map(|b| b.mNumberChannels)
+        .collect()
+}
+
+/// Returns the number of channels in each buffer of the device's stream configuration.
+pub fn stream_configuration(obj: AudioDeviceID, scope: Scope) -> Result<Vec<u32>, OSStatus> {
+    let data =
+        get_list_property_scoped::<u8>(obj, kAudioDevicePropertyStreamConfiguration, scope.into())?;
+    Ok(parse_buffer_list(&data))
+}
+
+/// Returns the total number of channels across all buffers in the device's stream configuration.
+pub fn channel_count(obj: AudioDeviceID, scope: Scope) -> Result<u32, OSStatus> {
+    stream_configuration(obj, scope).map(|buffers| buffers.iter().sum())
+}
+
+/// The latency `device` adds in `scope`, in milliseconds: the latency of its first stream, its
+/// safety offset and its buffer frame size.
+fn one_way_latency_ms(device: AudioDeviceID, scope: Scope) -> Result<f64, OSStatus> {
+    let scope: AudioObjectPropertyScope = scope.into();
+    let streams =
+        get_list_property_scoped::<AudioStreamID>(device, kAudioDevicePropertyStreams, scope)?;
+    let stream = *streams
+        .first()
+        .ok_or(kAudioHardwareBadStreamError as OSStatus)?;
+    let frames = get_property::<u32>(stream, kAudioStreamPropertyLatency)?
+        + get_property_scoped::<u32>(device, kAudioDevicePropertySafetyOffset, scope)?
+        + get_property_scoped::<u32>(device, kAudioDevicePropertyBufferFrameSize, scope)?;
+    let rate = get_property::<f64>(device, kAudioDevicePropertyNominalSampleRate)?;
+    Ok(frames as f64 * 1000.0 / rate)
+}
+
+/// Estimates the round-trip latency through the default input and output devices, in
+/// milliseconds, from their stream latencies, safety offsets and buffer frame sizes.
+pub fn round_trip_latency_estimate() -> Result<f64, OSStatus> {
+    #[allow(non_upper_case_globals)]
+    let default_device =
+        |selector| match get_property::<AudioDeviceID>(kAudioObjectSystemObject, selector)? {
+            kAudioObjectUnknown => Err(kAudioHardwareBadDeviceError as OSStatus),
+            device => Ok(device),
+        };
+    let input = default_device(kAudioHardwarePropertyDefaultInputDevice)?;
+    let output = default_device(kAudioHardwarePropertyDefaultOutputDevice)?;
+    Ok(one_way_latency_ms(input, Scope::Input)? + one_way_latency_ms(output, Scope::Output)?)
+}
+
+/// Follows `kAudioObjectPropertyOwner` from `obj` up to the system object, stopping early at a
+/// cycle or a failed read. `obj` itself is not included.
+pub fn owner_chain(obj: AudioObjectID) -> Vec<AudioObjectID> {
+    let mut chain = Vec::new();
+    let mut visited = HashSet::from([obj]);
+    let mut current = obj;
+    while current != kAudioObjectSystemObject {
+        match get_property::<AudioObjectID>(current, kAudioObjectPropertyOwner) {
+            Ok(owner) if owner != kAudioObjectUnknown && visited.insert(owner) => {
+                chain.push(owner);
+                current = owner;
+            }
+            _ => break,
+        }
+    }
+    chain
+}
+
+/// Walks up the owner chain of `obj` to the HAL plug-in that implements it.
+pub fn owning_plugin(obj: AudioObjectID) -> Result<AudioObjectID, OSStatus> {
+    owner_chain(obj)
+        .into_iter()
+        .find(|&owner| {
+            get_property::<AudioClassID>(owner, kAudioObjectPropertyClass)
+                .is_ok_and(|id| id == kAudioPlugInClassID)
+        })
+        .ok_or(kAudioHardwareBadObjectError as OSStatus)
+}
+
+/// Returns the name of `obj`, or its class and ID if it has no name.
+pub fn object_name(obj: AudioObjectID) -> String {
+    match get_string_property(obj, kAudioObjectPropertyName) {
+        Ok(name) if !name.is_empty() => name,
+        _ => match get_property::<AudioClassID>(obj, kAudioObjectPropertyClass)
+            .ok()
+            .and_then(class_to_str)
+        {
+            Some(class) => format!("{} {}", class, obj),
+            None => format!("AudioObjectID {}", obj),
+        },
+    }
+}
+
 fn class_to_str(obj: AudioClassID) -> Option<&'static str> {
     #[allow(non_upper_case_globals, non_snake_case)]
     match obj {
@@ -310,28 +883,174 @@ fn add_class_id(identifier: &str, id: Result<AudioClassID, OSStatus>) {
     add_leaf!("{} (FourCC): {:?}", identifier, CString::new(id.to_be_bytes().to_vec()).unwrap());
 }
 
-macro_rules! prop {
-    (@print $name: expr, $value: expr) => {
-        add_leaf!("{}: {:?}", $name, $value);
-    };
-    (@print @pretty $pretty: expr, $name: expr, $value: expr) => {
-        add_leaf!("{}: {:#?}", $name, $value);
-    };
-    (@internal $fun: expr $(, @pretty $pretty: expr)? $(, @prefix $prefix: expr)?, ($obj: expr, $prop: expr $(, $args: expr),*), $opt: expr $(, $map: expr)?) => {
-        let r = $fun($obj, $prop, $($args),*)$(.map($map))?;
-        let name = stringify!($prop).split("Property").last().unwrap();
-        $(let name = format!("{} {}", stringify!($prefix), name);)?
-        if $opt.contains(TraversalOptions::DEBUG) {
-            prop!(@print $(@pretty $pretty,)? name, r);
-        } else if let Ok(p) = r {
-            prop!(@print $(@pretty $pretty,)? name, p);
+static WRAP_WIDTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Truncates the lines of pretty-printed leaves to `cols` characters, or not at all for `None`.
+pub fn set_wrap_width(cols: Option<usize>) {
+    WRAP_WIDTH.store(cols.unwrap_or(0), Ordering::Relaxed);
+}
+
+fn wrap_lines(s: &str) -> String {
+    let width = WRAP_WIDTH.load(Ordering::Relaxed);
+    if width == 0 {
+        return s.to_string();
+    }
+    s.lines()
+        .map(|line| {
+            if line.chars().count() <= width {
+                return line.to_string();
+            }
+            let mut truncated: String = line.chars().take(width.saturating_sub(1)).collect();
+            truncated.push('…');
+            truncated
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+thread_local! {
+    static TRAVERSAL_ERRORS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts a failed read towards `traversal_errors`. Properties an object does not have are
+/// expected to fail and are not counted.
+fn record_error<T>(r: &Result<T, OSStatus>) {
+    match r {
+        Err(e) if *e != kAudioHardwareUnknownPropertyError as OSStatus => {
+            TRAVERSAL_ERRORS.with(|errors| errors.set(errors.get() + 1))
         }
-    };
-    (bool, Input, $prop: expr, $obj: expr, $opt: expr) => {
-        prop!(@internal get_property_scoped::<u32>, @prefix Input, ($obj, $prop, kAudioObjectPropertyScopeInput), $opt, |p| p != 0);
-    };
+        _ => {}
+    }
+}
+
+/// Returns how many property reads failed, for other reasons than the property not existing,
+/// across all traversals on this thread.
+pub fn traversal_errors() -> usize {
+    TRAVERSAL_ERRORS.with(Cell::get)
+}
+
+thread_local! {
+    /// The objects currently being traversed, outermost first, with their classes.
+    static TRAVERSAL_PATH: RefCell<Vec<(AudioObjectID, Result<AudioClassID, OSStatus>)>> =
+        const { RefCell::new(Vec::new()) };
+    /// The property currently being read, if any.
+    static CURRENT_PROPERTY: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+/// Keeps `obj` on the traversal path until dropped.
+struct PathGuard;
+
+impl PathGuard {
+    fn push(obj: AudioObjectID, class_id: Result<AudioClassID, OSStatus>) -> Self {
+        TRAVERSAL_PATH.with(|path| path.borrow_mut().push((obj, class_id)));
+        PathGuard
+    }
+}
+
+impl Drop for PathGuard {
+    fn drop(&mut self) {
+        TRAVERSAL_PATH.with(|path| path.borrow_mut().pop());
+    }
+}
+
+/// Installs a panic hook that reports which property of which object was being read when a
+/// traversal panicked, before deferring to the previous hook.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let current = TRAVERSAL_PATH.with(|path| path.borrow().last().copied());
+        if let Some((obj, class_id)) = current {
+            let class = match class_id {
+                Ok(id) => class_to_str(id).map_or_else(|| fourcc_to_string(id), String::from),
+                Err(e) => format!("Err({})", e),
+            };
+            match CURRENT_PROPERTY.with(Cell::get) {
+                Some(property) => eprintln!(
+                    "panicked while reading {} of object {} (class {})",
+                    property, obj, class
+                ),
+                None => eprintln!("panicked while traversing object {} (class {})", obj, class),
+            }
+        }
+        previous(info);
+    }));
+}
+
+/// Renders a property address so a failed read can be reproduced with other tools.
+fn address_to_string(selector: u32, scope: u32, element: u32) -> String {
+    format!(
+        "{{ selector: {}, scope: {}, element: {} }}",
+        fourcc_to_string(selector),
+        scope_to_str(scope),
+        element
+    )
+}
+
+/// Whether properties in `scope` should be read, given the INPUT_ONLY and OUTPUT_ONLY options.
+fn scope_enabled(scope: Scope, opt: TraversalOptions) -> bool {
+    match scope {
+        Scope::Input => !opt.contains(TraversalOptions::OUTPUT_ONLY),
+        Scope::Output => !opt.contains(TraversalOptions::INPUT_ONLY),
+        Scope::Global | Scope::PlayThrough => true,
+    }
+}
+
+/// Whether a property value read by `prop!` is an empty string, which HIDE_EMPTY leaves out.
+fn is_empty_string(value: &dyn Any) -> bool {
+    value.downcast_ref::<String>().is_some_and(String::is_empty)
+}
+
+/// A value `prop!` prints as is, for maps that already format it, like "50%". Plain Strings would
+/// be printed quoted.
+struct Formatted(String);
+
+impl fmt::Debug for Formatted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+macro_rules! prop {
+    (@print $name: expr, $value: expr) => {
+        let value = format!("{:?}", $value);
+        emit(|sink| sink.property(&$name, &value));
+    };
+    (@print @pretty $pretty: expr, $name: expr, $value: expr) => {
+        let value = wrap_lines(&format!("{:#?}", $value));
+        emit(|sink| sink.property(&$name, &value));
+    };
+    (@internal $fun: expr $(, @pretty $pretty: expr)? $(, @prefix $prefix: expr)?, ($obj: expr, $prop: expr $(, $args: expr),*), $opt: expr $(, $map: expr)?) => {
+        CURRENT_PROPERTY.with(|p| p.set(Some(stringify!($prop))));
+        let r = $fun($obj, $prop, $($args),*)$(.map($map))?;
+        CURRENT_PROPERTY.with(|p| p.set(None));
+        record_error(&r);
+        let name = stringify!($prop).split("Property").last().unwrap();
+        $(let name = format!("{} {}", stringify!($prefix), name);)?
+        if let (true, Err(e)) = ($opt.contains(TraversalOptions::DEBUG), &r) {
+            // Scoped getters take the scope as their only extra argument.
+            let scope = [$($args),*].first().copied().unwrap_or(kAudioObjectPropertyScopeGlobal);
+            let value = format!("Err({}) at {}", e, address_to_string($prop, scope, kAudioObjectPropertyElementMaster));
+            emit(|sink| sink.property(&name, &value));
+        } else if $opt.contains(TraversalOptions::DEBUG) {
+            prop!(@print $(@pretty $pretty,)? name, r);
+        } else if let Ok(p) = r {
+            if !($opt.contains(TraversalOptions::HIDE_EMPTY) && is_empty_string(&p)) {
+                prop!(@print $(@pretty $pretty,)? name, p);
+            }
+        }
+    };
+    (bool, Input, $prop: expr, $obj: expr, $opt: expr) => {
+        if scope_enabled(Scope::Input, $opt) {
+            prop!(@internal get_property_scoped::<u32>, @prefix Input, ($obj, $prop, kAudioObjectPropertyScopeInput), $opt, |p| p != 0);
+        }
+    };
     (bool, Output, $prop: expr, $obj: expr, $opt: expr) => {
-        prop!(@internal get_property_scoped::<u32>, @prefix Output, ($obj, $prop, kAudioObjectPropertyScopeOutput), $opt, |p| p != 0);
+        if scope_enabled(Scope::Output, $opt) {
+            prop!(@internal get_property_scoped::<u32>, @prefix Output, ($obj, $prop, kAudioObjectPropertyScopeOutput), $opt, |p| p != 0);
+        }
+    };
+    (bool, PlayThrough, $prop: expr, $obj: expr, $opt: expr) => {
+        prop!(@internal get_property_scoped::<u32>, @prefix PlayThrough, ($obj, $prop, kAudioObjectPropertyScopePlayThrough), $opt, |p| p != 0);
     };
     (bool, $prop: expr, $obj: expr, $opt: expr) => {
         prop!(@internal get_property::<u32>, ($obj, $prop), $opt, |p| p != 0);
@@ -339,17 +1058,30 @@ macro_rules! prop {
     (string, $prop: expr, $obj: expr, $opt: expr) => {
         prop!(@internal get_string_property, ($obj, $prop), $opt);
     };
+    (ChannelLayout, Output, $prop: expr, $obj: expr, $opt: expr) => {
+        if scope_enabled(Scope::Output, $opt) {
+            prop!(@internal get_channel_layout_scoped, @pretty "", @prefix Output, ($obj, $prop, kAudioObjectPropertyScopeOutput), $opt);
+        }
+    };
     (Vec<$t: ty>, Pretty, Input, $prop: expr, $obj: expr, $opt: expr $(, $map: expr)?) => {
-        prop!(@internal get_list_property_scoped::<$t>, @pretty "", @prefix Input, ($obj, $prop, kAudioObjectPropertyScopeInput), $opt$(, $map)?);
+        if scope_enabled(Scope::Input, $opt) {
+            prop!(@internal get_list_property_scoped::<$t>, @pretty "", @prefix Input, ($obj, $prop, kAudioObjectPropertyScopeInput), $opt$(, $map)?);
+        }
     };
     (Vec<$t: ty>, Pretty, Output, $prop: expr, $obj: expr, $opt: expr $(, $map: expr)?) => {
-        prop!(@internal get_list_property_scoped::<$t>, @pretty "", @prefix Output, ($obj, $prop, kAudioObjectPropertyScopeOutput), $opt$(, $map)?);
+        if scope_enabled(Scope::Output, $opt) {
+            prop!(@internal get_list_property_scoped::<$t>, @pretty "", @prefix Output, ($obj, $prop, kAudioObjectPropertyScopeOutput), $opt$(, $map)?);
+        }
     };
     (Vec<$t: ty>, Input, $prop: expr, $obj: expr, $opt: expr $(, $map: expr)?) => {
-        prop!(@internal get_list_property_scoped::<$t>, @prefix Input, ($obj, $prop, kAudioObjectPropertyScopeInput), $opt$(, $map)?);
+        if scope_enabled(Scope::Input, $opt) {
+            prop!(@internal get_list_property_scoped::<$t>, @prefix Input, ($obj, $prop, kAudioObjectPropertyScopeInput), $opt$(, $map)?);
+        }
     };
     (Vec<$t: ty>, Output, $prop: expr, $obj: expr, $opt: expr $(, $map: expr)?) => {
-        prop!(@internal get_list_property_scoped::<$t>, @prefix Output, ($obj, $prop, kAudioObjectPropertyScopeOutput), $opt$(, $map)?);
+        if scope_enabled(Scope::Output, $opt) {
+            prop!(@internal get_list_property_scoped::<$t>, @prefix Output, ($obj, $prop, kAudioObjectPropertyScopeOutput), $opt$(, $map)?);
+        }
     };
     (Vec<$t: ty>, Pretty, $prop: expr, $obj: expr, $opt: expr $(, $map: expr)?) => {
         prop!(@internal get_list_property::<$t>, @pretty "", ($obj, $prop), $opt$(, $map)?);
@@ -358,16 +1090,27 @@ macro_rules! prop {
         prop!(@internal get_list_property::<$t>, ($obj, $prop), $opt$(, $map)?);
     };
     ($t: ty, Pretty, Input, $prop: expr, $obj: expr, $opt: expr $(, $map: expr)?) => {
-        prop!(@internal get_property_scoped::<$t>, @pretty "", @prefix Input, ($obj, $prop, kAudioObjectPropertyScopeInput), $opt$(, $map)?);
+        if scope_enabled(Scope::Input, $opt) {
+            prop!(@internal get_property_scoped::<$t>, @pretty "", @prefix Input, ($obj, $prop, kAudioObjectPropertyScopeInput), $opt$(, $map)?);
+        }
     };
     ($t: ty, Pretty, Output, $prop: expr, $obj: expr, $opt: expr $(, $map: expr)?) => {
-        prop!(@internal get_property_scoped::<$t>, @pretty "", @prefix Output, ($obj, $prop, kAudioObjectPropertyScopeOutput), $opt$(, $map)?);
+        if scope_enabled(Scope::Output, $opt) {
+            prop!(@internal get_property_scoped::<$t>, @pretty "", @prefix Output, ($obj, $prop, kAudioObjectPropertyScopeOutput), $opt$(, $map)?);
+        }
     };
     ($t: ty, Input, $prop: expr, $obj: expr, $opt: expr $(, $map: expr)?) => {
-        prop!(@internal get_property_scoped::<$t>, @prefix Input, ($obj, $prop, kAudioObjectPropertyScopeInput), $opt$(, $map)?);
+        if scope_enabled(Scope::Input, $opt) {
+            prop!(@internal get_property_scoped::<$t>, @prefix Input, ($obj, $prop, kAudioObjectPropertyScopeInput), $opt$(, $map)?);
+        }
     };
     ($t: ty, Output, $prop: expr, $obj: expr, $opt: expr $(, $map: expr)?) => {
-        prop!(@internal get_property_scoped::<$t>, @prefix Output, ($obj, $prop, kAudioObjectPropertyScopeOutput), $opt$(, $map)?);
+        if scope_enabled(Scope::Output, $opt) {
+            prop!(@internal get_property_scoped::<$t>, @prefix Output, ($obj, $prop, kAudioObjectPropertyScopeOutput), $opt$(, $map)?);
+        }
+    };
+    ($t: ty, PlayThrough, $prop: expr, $obj: expr, $opt: expr $(, $map: expr)?) => {
+        prop!(@internal get_property_scoped::<$t>, @prefix PlayThrough, ($obj, $prop, kAudioObjectPropertyScopePlayThrough), $opt$(, $map)?);
     };
     ($t: ty, Pretty, $prop: expr, $obj: expr, $opt: expr $(, $map: expr)?) => {
         prop!(@internal get_property::<$t>, @pretty "", ($obj, $prop), $opt$(, $map)?);
@@ -385,9 +1128,279 @@ fn cfarray_get_count(r: usize) -> usize {
     unsafe { CFArrayGetCount(arr) as usize }
 }
 
+/// Reads a CFArray of CFStrings into Strings, releasing the array.
+fn cfarray_strings(r: usize) -> Vec<String> {
+    let arr = r as CFArrayRef;
+    if arr.is_null() {
+        return Vec::new();
+    }
+    let strings = (0..unsafe { CFArrayGetCount(arr) })
+        .map(|i| unsafe { CFArrayGetValueAtIndex(arr, i) } as CFStringRef)
+        .map(|s| String::from_utf8_lossy(&utf8_from_cfstringref(s)).into_owned())
+        .collect();
+    unsafe { CFRelease(arr as CFTypeRef) };
+    strings
+}
+
+fn cftype_description(r: usize) -> String {
+    let obj = r as CFTypeRef;
+    if obj.is_null() {
+        return "NULL".to_string();
+    }
+    let description = StringRef::new(unsafe { CFCopyDescription(obj) });
+    unsafe { CFRelease(obj) };
+    description.into_string()
+}
+
+/// Renders the bit depth, and the container size too if the samples don't fill it, e.g.
+/// "24-bit/32-bit container" for 24-bit samples in 4-byte containers.
+fn bit_depth(asbd: &AudioStreamBasicDescription) -> String {
+    let channels_per_buffer = if asbd.mFormatFlags & kAudioFormatFlagIsNonInterleaved != 0 {
+        1
+    } else {
+        asbd.mChannelsPerFrame
+    };
+    let container_bits = match channels_per_buffer {
+        0 => 0,
+        n => asbd.mBytesPerFrame / n * 8,
+    };
+    if container_bits == 0 || container_bits == asbd.mBitsPerChannel {
+        format!("{}-bit", asbd.mBitsPerChannel)
+    } else {
+        format!("{}-bit/{}-bit container", asbd.mBitsPerChannel, container_bits)
+    }
+}
+
+/// Zeroes the fields of an ASBD that carry no meaning, so that two captures of the same format
+/// render identically. ASBDs have no padding, so that is only `mReserved`.
+pub fn normalize_asbd(asbd: AudioStreamBasicDescription) -> AudioStreamBasicDescription {
+    AudioStreamBasicDescription {
+        mReserved: 0,
+        ..asbd
+    }
+}
+
+fn normalize_ranged_asbds(
+    formats: Vec<AudioStreamRangedDescription>,
+) -> Vec<AudioStreamRangedDescription> {
+    formats
+        .into_iter()
+        .map(|ranged| AudioStreamRangedDescription {
+            mFormat: normalize_asbd(ranged.mFormat),
+            ..ranged
+        })
+        .collect()
+}
+
+/// Whether two ASBDs describe the same format, ignoring fields that carry no meaning.
+pub fn asbd_equivalent(a: &AudioStreamBasicDescription, b: &AudioStreamBasicDescription) -> bool {
+    a.mSampleRate == b.mSampleRate
+        && a.mFormatID == b.mFormatID
+        && a.mFormatFlags == b.mFormatFlags
+        && a.mBytesPerPacket == b.mBytesPerPacket
+        && a.mFramesPerPacket == b.mFramesPerPacket
+        && a.mBytesPerFrame == b.mBytesPerFrame
+        && a.mChannelsPerFrame == b.mChannelsPerFrame
+        && a.mBitsPerChannel == b.mBitsPerChannel
+}
+
+/// Summarizes an ASBD on one line, like "48000 Hz, 2 ch, 32-bit float, interleaved, little-endian".
+pub fn asbd_summary(asbd: AudioStreamBasicDescription) -> String {
+    let flags = asbd.mFormatFlags;
+    let kind = if asbd.mFormatID != kAudioFormatLinearPCM {
+        fourcc_to_string(asbd.mFormatID)
+    } else if flags & kAudioFormatFlagIsFloat != 0 {
+        format!("{} float", bit_depth(&asbd))
+    } else if flags & kAudioFormatFlagIsSignedInteger != 0 {
+        format!("{} signed integer", bit_depth(&asbd))
+    } else {
+        format!("{} unsigned integer", bit_depth(&asbd))
+    };
+    format!(
+        "{} Hz, {} ch, {}, {}, {}",
+        asbd.mSampleRate,
+        asbd.mChannelsPerFrame,
+        kind,
+        if flags & kAudioFormatFlagIsNonInterleaved != 0 {
+            "non-interleaved"
+        } else {
+            "interleaved"
+        },
+        if flags & kAudioFormatFlagIsBigEndian != 0 {
+            "big-endian"
+        } else {
+            "little-endian"
+        }
+    )
+}
+
+fn traverse_tap(obj: AudioObjectID, opt: TraversalOptions) {
+    prop!(string, kAudioTapPropertyUID, obj, opt);
+    prop!(usize, kAudioTapPropertyDescription, obj, opt, cftype_description);
+    prop!(AudioStreamBasicDescription, kAudioTapPropertyFormat, obj, opt, asbd_summary);
+}
+
+fn driftcompensationquality_to_str(q: u32) -> &'static str {
+    #[allow(non_upper_case_globals, non_snake_case)]
+    match q {
+        kAudioSubDeviceDriftCompensationMinQuality => "Min",
+        kAudioSubDeviceDriftCompensationLowQuality => "Low",
+        kAudioSubDeviceDriftCompensationMediumQuality => "Medium",
+        kAudioSubDeviceDriftCompensationHighQuality => "High",
+        kAudioSubDeviceDriftCompensationMaxQuality => "Max",
+        _ => "Unexpected DriftCompensationQuality",
+    }
+}
+
+fn traverse_subdevice(obj: AudioObjectID, opt: TraversalOptions) {
+    prop!(f64, kAudioSubDevicePropertyExtraLatency, obj, opt);
+    prop!(bool, kAudioSubDevicePropertyDriftCompensation, obj, opt);
+    prop!(
+        u32,
+        kAudioSubDevicePropertyDriftCompensationQuality,
+        obj,
+        opt,
+        driftcompensationquality_to_str
+    );
+}
+
+fn traverse_subtap(obj: AudioObjectID, opt: TraversalOptions) {
+    add_branch!("AudioObjectID: {}", obj);
+    prop!(string, kAudioTapPropertyUID, obj, opt);
+    prop!(f64, kAudioSubTapPropertyExtraLatency, obj, opt);
+    prop!(bool, kAudioSubTapPropertyDriftCompensation, obj, opt);
+    prop!(
+        u32,
+        kAudioSubTapPropertyDriftCompensationQuality,
+        obj,
+        opt,
+        driftcompensationquality_to_str
+    );
+}
+
 fn traverse_aggregate_device(obj: AudioObjectID, opt: TraversalOptions) {
+    prop!(usize, kAudioAggregateDevicePropertyFullSubDeviceList, obj, opt, cfarray_strings);
     prop!(usize, kAudioAggregateDevicePropertyTapList, obj, opt, cfarray_get_count);
     prop!(usize, kAudioAggregateDevicePropertySubTapList, obj, opt, cfarray_get_count);
+    let taps = get_cfstring_array_property(
+        obj,
+        kAudioAggregateDevicePropertyTapList,
+        kAudioObjectPropertyScopeGlobal,
+    )
+    .unwrap_or_default();
+    if !taps.is_empty() {
+        add_branch!("Taps");
+        for uid in taps {
+            match tap_for_uid(&uid) {
+                Ok(tap) => traverse_obj(tap, opt),
+                Err(e) => add_leaf!("{}: Err({})", uid, e),
+            }
+        }
+    }
+    let subtaps: Vec<AudioObjectID> =
+        get_list_property::<AudioObjectID>(obj, kAudioObjectPropertyOwnedObjects)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|&o| {
+                get_property::<AudioClassID>(o, kAudioObjectPropertyClass)
+                    == Ok(kAudioSubTapClassID)
+            })
+            .collect();
+    if !subtaps.is_empty() {
+        add_branch!("Sub-taps");
+        for subtap in subtaps {
+            traverse_subtap(subtap, opt);
+        }
+    }
+}
+
+// From AudioServerPlugIn.h, which coreaudio-sys doesn't bind.
+#[allow(non_upper_case_globals)]
+const kAudioObjectPropertyCustomPropertyInfoList: AudioObjectPropertySelector = 0x63757374; // 'cust'
+#[allow(non_upper_case_globals)]
+const kAudioServerPlugInCustomPropertyDataTypeNone: u32 = 0;
+#[allow(non_upper_case_globals)]
+const kAudioServerPlugInCustomPropertyDataTypeCFString: u32 = 0x63667374; // 'cfst'
+#[allow(non_upper_case_globals)]
+const kAudioServerPlugInCustomPropertyDataTypeCFPropertyList: u32 = 0x706c7374; // 'plst'
+
+#[repr(C)]
+#[allow(non_snake_case)]
+#[derive(Clone, Copy, Debug, Default)]
+struct AudioServerPlugInCustomPropertyInfo {
+    mSelector: AudioObjectPropertySelector,
+    mPropertyDataType: u32,
+    mQualifierDataType: u32,
+}
+
+fn customdatatype_to_str(t: u32) -> String {
+    if t == kAudioServerPlugInCustomPropertyDataTypeNone {
+        String::from("None")
+    } else {
+        fourcc_to_string(t)
+    }
+}
+
+/// How much of a property `hexdump` shows.
+const HEXDUMP_MAX_LEN: usize = 256;
+
+/// Renders up to `HEXDUMP_MAX_LEN` bytes, 16 per line, prefixed with their offset.
+fn hexdump(bytes: &[u8]) -> String {
+    let mut lines: Vec<String> = bytes[..bytes.len().min(HEXDUMP_MAX_LEN)]
+        .chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("{:04x}: {}", i * 16, hex.join(" "))
+        })
+        .collect();
+    if bytes.len() > HEXDUMP_MAX_LEN {
+        lines.push(format!("... ({} bytes in total)", bytes.len()));
+    }
+    lines.join("\n")
+}
+
+fn traverse_custom_properties(obj: AudioObjectID, opt: TraversalOptions) {
+    let Ok(infos) = get_list_property::<AudioServerPlugInCustomPropertyInfo>(
+        obj,
+        kAudioObjectPropertyCustomPropertyInfoList,
+    ) else {
+        return;
+    };
+    if infos.is_empty() {
+        return;
+    }
+    add_branch!("CustomProperties");
+    for info in infos {
+        add_leaf!(
+            "{}: data {}, qualifier {}",
+            fourcc_to_string(info.mSelector),
+            customdatatype_to_str(info.mPropertyDataType),
+            customdatatype_to_str(info.mQualifierDataType)
+        );
+        // CFString and CFPropertyList data is a CF reference, whose bytes mean nothing. Data of
+        // other types is whatever the driver put there. Those needing a qualifier can't be read.
+        let untyped = info.mPropertyDataType != kAudioServerPlugInCustomPropertyDataTypeCFString
+            && info.mPropertyDataType != kAudioServerPlugInCustomPropertyDataTypeCFPropertyList;
+        if opt.contains(TraversalOptions::HEXDUMP)
+            && untyped
+            && info.mQualifierDataType == kAudioServerPlugInCustomPropertyDataTypeNone
+        {
+            match get_list_property::<u8>(obj, info.mSelector) {
+                Ok(bytes) => {
+                    add_leaf!("{} raw:\n{}", fourcc_to_string(info.mSelector), hexdump(&bytes))
+                }
+                Err(e) => add_leaf!("{} raw: Err({})", fourcc_to_string(info.mSelector), e),
+            }
+        }
+    }
+}
+
+fn traverse_endpoint_device(obj: AudioObjectID, opt: TraversalOptions) {
+    prop!(usize, kAudioEndPointDevicePropertyComposition, obj, opt, cftype_description);
+    prop!(Vec<AudioObjectID>, kAudioEndPointDevicePropertyEndPointList, obj, opt);
+    // The pid of the process owning the device, or 0 if it is public.
+    prop!(pid_t, kAudioEndPointDevicePropertyIsPrivate, obj, opt);
 }
 
 fn transporttype_to_str(p: u32) -> &'static str {
@@ -414,6 +1427,49 @@ fn transporttype_to_str(p: u32) -> &'static str {
     }
 }
 
+/// A coarse grouping of transport types, e.g. for picking an icon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransportCategory {
+    BuiltIn,
+    Usb,
+    Bluetooth,
+    /// Virtual and aggregate devices.
+    Virtual,
+    /// Audio carried to a display, over HDMI or DisplayPort.
+    Display,
+    /// AirPlay, AVB and Continuity Capture.
+    Network,
+    /// PCI, FireWire and Thunderbolt interfaces.
+    Interface,
+    Unknown,
+}
+
+pub fn transport_category(transport: u32) -> TransportCategory {
+    #[allow(non_upper_case_globals, non_snake_case)]
+    match transport {
+        kAudioDeviceTransportTypeBuiltIn => TransportCategory::BuiltIn,
+        kAudioDeviceTransportTypeUSB => TransportCategory::Usb,
+        kAudioDeviceTransportTypeBluetooth | kAudioDeviceTransportTypeBluetoothLE => {
+            TransportCategory::Bluetooth
+        }
+        kAudioDeviceTransportTypeAggregate | kAudioDeviceTransportTypeVirtual => {
+            TransportCategory::Virtual
+        }
+        kAudioDeviceTransportTypeHDMI | kAudioDeviceTransportTypeDisplayPort => {
+            TransportCategory::Display
+        }
+        kAudioDeviceTransportTypeAirPlay
+        | kAudioDeviceTransportTypeAVB
+        | kAudioDeviceTransportTypeContinuityCaptureWired
+        | kAudioDeviceTransportTypeContinuityCaptureWireless
+        | kAudioDeviceTransportTypeContinuityCapture => TransportCategory::Network,
+        kAudioDeviceTransportTypePCI
+        | kAudioDeviceTransportTypeFireWire
+        | kAudioDeviceTransportTypeThunderbolt => TransportCategory::Interface,
+        _ => TransportCategory::Unknown,
+    }
+}
+
 #[derive(Debug, Clone)]
 #[allow(non_camel_case_types, non_snake_case, dead_code)]
 struct AudioChannelLayout_ExpandedChannels {
@@ -434,21 +1490,287 @@ impl AudioChannelLayout_ExpandedChannels {
     }
 }
 
-fn expand_channel_layout(data: Vec<u8>) -> AudioChannelLayout_ExpandedChannels {
+/// Parses the raw bytes of an `AudioChannelLayout`. Only the descriptions both declared and held
+/// by `data` are kept, as layouts given by a tag or bitmap typically declare none but still come
+/// with room for one.
+fn expand_channel_layout(data: Vec<u8>) -> Result<AudioChannelLayout_ExpandedChannels, OSStatus> {
     let acl_len = mem::size_of::<AudioChannelLayout>();
     let acd_len = mem::size_of::<AudioChannelDescription>();
     let acl_base_len = acl_len - acd_len;
-    assert!(data.len() >= acl_base_len);
-    let layout_ptr = data.as_ptr() as *const AudioChannelLayout;
-    let num_channels = (data.len() - acl_base_len) / acd_len;
-    debug_assert_eq!(unsafe { *layout_ptr }.mNumberChannelDescriptions as usize, num_channels);
-    let cs = unsafe {
-        std::slice::from_raw_parts(
-            (data.as_ptr().wrapping_add(acl_base_len)) as *const AudioChannelDescription,
-            num_channels,
+    if data.len() < acl_base_len {
+        return Err(kAudioHardwareBadPropertySizeError as OSStatus);
+    }
+    // Only read the header, as the layout may be shorter than an AudioChannelLayout.
+    let mut layout: AudioChannelLayout = unsafe { mem::zeroed() };
+    unsafe {
+        ptr::copy_nonoverlapping(
+            data.as_ptr(),
+            &mut layout as *mut AudioChannelLayout as *mut u8,
+            acl_base_len,
         )
     };
-    AudioChannelLayout_ExpandedChannels::new(unsafe { *layout_ptr }, cs.into())
+    let num_channels =
+        (layout.mNumberChannelDescriptions as usize).min((data.len() - acl_base_len) / acd_len);
+    let cs = (0..num_channels)
+        .map(|i| unsafe {
+            ptr::read_unaligned(data.as_ptr().wrapping_add(acl_base_len + i * acd_len)
+                as *const AudioChannelDescription)
+        })
+        .collect();
+    Ok(AudioChannelLayout_ExpandedChannels::new(layout, cs))
+}
+
+fn get_channel_layout_scoped(
+    obj: AudioObjectID,
+    selector: u32,
+    scope: u32,
+) -> Result<AudioChannelLayout_ExpandedChannels, OSStatus> {
+    get_list_property_scoped::<u8>(obj, selector, scope).and_then(expand_channel_layout)
+}
+
+/// The number of channels a layout describes, whether through descriptions, a bitmap or a tag.
+fn layout_channel_count(layout: &AudioChannelLayout_ExpandedChannels) -> u32 {
+    #[allow(non_upper_case_globals)]
+    match layout.mChannelLayoutTag {
+        kAudioChannelLayoutTag_UseChannelDescriptions => layout.mNumberChannelDescriptions,
+        kAudioChannelLayoutTag_UseChannelBitmap => layout.mChannelBitmap.count_ones(),
+        // The low 16 bits of other tags are the channel count.
+        tag => tag & 0xFFFF,
+    }
+}
+
+/// Warns when the preferred output layout disagrees with the output stream configuration about
+/// the number of channels, which confuses downmixing.
+fn check_preferred_layout(obj: AudioDeviceID, opt: TraversalOptions) {
+    if !scope_enabled(Scope::Output, opt) {
+        return;
+    }
+    let (Ok(layout), Ok(channels)) = (
+        get_channel_layout_scoped(
+            obj,
+            kAudioDevicePropertyPreferredChannelLayout,
+            kAudioObjectPropertyScopeOutput,
+        ),
+        channel_count(obj, Scope::Output),
+    ) else {
+        return;
+    };
+    if layout.mChannelDescriptions.len() < layout.mNumberChannelDescriptions as usize {
+        add_leaf!(
+            "WARNING: preferred layout declares {} channel descriptions but only holds {}",
+            layout.mNumberChannelDescriptions,
+            layout.mChannelDescriptions.len()
+        );
+    }
+    let layout_channels = layout_channel_count(&layout);
+    if channels > 0 && layout_channels != channels {
+        add_leaf!(
+            "WARNING: preferred layout has {} channels but device reports {} output channels",
+            layout_channels,
+            channels
+        );
+    }
+}
+
+fn channellabel_to_str(label: AudioChannelLabel) -> Option<&'static str> {
+    #[allow(non_upper_case_globals, non_snake_case)]
+    match label {
+        kAudioChannelLabel_Left => Some("L"),
+        kAudioChannelLabel_Right => Some("R"),
+        kAudioChannelLabel_Center => Some("C"),
+        kAudioChannelLabel_LFEScreen => Some("LFE"),
+        kAudioChannelLabel_LeftSurround => Some("Ls"),
+        kAudioChannelLabel_RightSurround => Some("Rs"),
+        kAudioChannelLabel_LeftCenter => Some("Lc"),
+        kAudioChannelLabel_RightCenter => Some("Rc"),
+        kAudioChannelLabel_CenterSurround => Some("Cs"),
+        kAudioChannelLabel_LeftSurroundDirect => Some("Lsd"),
+        kAudioChannelLabel_RightSurroundDirect => Some("Rsd"),
+        kAudioChannelLabel_TopCenterSurround => Some("Ts"),
+        kAudioChannelLabel_VerticalHeightLeft => Some("Vhl"),
+        kAudioChannelLabel_VerticalHeightCenter => Some("Vhc"),
+        kAudioChannelLabel_VerticalHeightRight => Some("Vhr"),
+        kAudioChannelLabel_RearSurroundLeft => Some("Rls"),
+        kAudioChannelLabel_RearSurroundRight => Some("Rrs"),
+        kAudioChannelLabel_LFE2 => Some("LFE2"),
+        kAudioChannelLabel_Mono => Some("M"),
+        _ => None,
+    }
+}
+
+/// Renders a layout on one line, like "7.1 (L R C LFE Ls Rs Rls Rrs)", or None if it isn't
+/// described per channel or has labels we can't name.
+fn compact_channel_layout(layout: &AudioChannelLayout_ExpandedChannels) -> Option<String> {
+    if layout.mChannelDescriptions.is_empty() {
+        return None;
+    }
+    let labels = layout
+        .mChannelDescriptions
+        .iter()
+        .map(|d| channellabel_to_str(d.mChannelLabel))
+        .collect::<Option<Vec<_>>>()?;
+    let lfe = labels.iter().filter(|l| l.starts_with("LFE")).count();
+    Some(format!("{}.{} ({})", labels.len() - lfe, lfe, labels.join(" ")))
+}
+
+fn add_stream_configuration(obj: AudioObjectID, scope: Scope, opt: TraversalOptions) {
+    if !scope_enabled(scope, opt) {
+        return;
+    }
+    let r = stream_configuration(obj, scope);
+    let name = format!("{} Buffers", scope);
+    if opt.contains(TraversalOptions::DEBUG) {
+        add_leaf!("{}: {:?}", name, r);
+    } else if let Ok(buffers) = r {
+        add_leaf!("{}: {:?}", name, buffers);
+    }
+}
+
+fn rate_bounds(ranges: &[AudioValueRange]) -> Vec<(f64, f64)> {
+    ranges.iter().map(|r| (r.mMinimum, r.mMaximum)).collect()
+}
+
+/// Lists the names the device gives each channel element in `scope`, like "Front Left".
+fn add_element_names(obj: AudioObjectID, scope: Scope, opt: TraversalOptions) {
+    if !scope_enabled(scope, opt) {
+        return;
+    }
+    let Ok(channels) = channel_count(obj, scope) else {
+        return;
+    };
+    let lines: Vec<String> = (1..=channels)
+        .filter_map(|element| {
+            let read = |selector| {
+                get_string_property_element(obj, selector, scope.into(), element)
+                    .ok()
+                    .filter(|s| !s.is_empty())
+            };
+            let mut parts = Vec::new();
+            if let Some(name) = read(kAudioObjectPropertyElementName) {
+                parts.push(name);
+            }
+            if let Some(category) = read(kAudioObjectPropertyElementCategoryName) {
+                parts.push(format!("category {}", category));
+            }
+            if let Some(number) = read(kAudioObjectPropertyElementNumberName) {
+                parts.push(format!("number {}", number));
+            }
+            (!parts.is_empty()).then(|| format!("Element {}: {}", element, parts.join(", ")))
+        })
+        .collect();
+    if lines.is_empty() {
+        return;
+    }
+    add_branch!("{} Element names", scope);
+    for line in lines {
+        add_leaf!("{}", line);
+    }
+}
+
+/// Some drivers report a preferred stereo pair that isn't a pair. Only show valid pairs, unless
+/// debugging.
+fn add_preferred_stereo(obj: AudioObjectID, scope: Scope, opt: TraversalOptions) {
+    if !scope_enabled(scope, opt) {
+        return;
+    }
+    let r = get_list_property_scoped::<u32>(
+        obj,
+        kAudioDevicePropertyPreferredChannelsForStereo,
+        scope.into(),
+    );
+    record_error(&r);
+    let debug = opt.contains(TraversalOptions::DEBUG);
+    match r {
+        Ok(channels) if channels.len() == 2 => {
+            add_leaf!("{} Preferred stereo: L={}, R={}", scope, channels[0], channels[1])
+        }
+        Ok(channels) if debug => {
+            add_leaf!("{} Preferred stereo: (invalid, {} elements)", scope, channels.len())
+        }
+        Err(e) if debug => add_leaf!("{} Preferred stereo: Err({})", scope, e),
+        _ => {}
+    }
+}
+
+/// Shows the main element's volume in dB within the range it can take, e.g.
+/// "Output Volume: -6.0 dB (range -96.0 .. 0.0)".
+fn add_volume_decibels(obj: AudioObjectID, scope: Scope, opt: TraversalOptions) {
+    if !scope_enabled(scope, opt) {
+        return;
+    }
+    let volume = get_property_scoped::<f32>(obj, kAudioDevicePropertyVolumeDecibels, scope.into());
+    let range = get_property_scoped::<AudioValueRange>(
+        obj,
+        kAudioDevicePropertyVolumeRangeDecibels,
+        scope.into(),
+    );
+    record_error(&volume);
+    record_error(&range);
+    match (volume, range) {
+        (Ok(volume), Ok(range)) => add_leaf!(
+            "{} Volume: {:.1} dB (range {:.1} .. {:.1})",
+            scope,
+            volume,
+            range.mMinimum,
+            range.mMaximum
+        ),
+        (Ok(volume), Err(_)) => add_leaf!("{} Volume: {:.1} dB", scope, volume),
+        (Err(e), _) if opt.contains(TraversalOptions::DEBUG) => {
+            add_leaf!("{} Volume: Err({})", scope, e)
+        }
+        _ => {}
+    }
+}
+
+/// Warns about streams whose virtual format rate differs from the device's nominal rate, which
+/// means a rate change was caught halfway or the driver is inconsistent.
+fn check_stream_rates(obj: AudioDeviceID, opt: TraversalOptions) {
+    let Ok(nominal) = get_property::<f64>(obj, kAudioDevicePropertyNominalSampleRate) else {
+        return;
+    };
+    for scope in [Scope::Input, Scope::Output] {
+        if !scope_enabled(scope, opt) {
+            continue;
+        }
+        let streams = get_list_property_scoped::<AudioStreamID>(
+            obj,
+            kAudioDevicePropertyStreams,
+            scope.into(),
+        )
+        .unwrap_or_default();
+        for stream in streams {
+            if let Ok(format) = get_property::<AudioStreamBasicDescription>(
+                stream,
+                kAudioStreamPropertyVirtualFormat,
+            ) {
+                if format.mSampleRate != nominal {
+                    add_leaf!(
+                        "WARNING: stream {} rate {} != device nominal {}",
+                        stream,
+                        format.mSampleRate,
+                        nominal
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Devices that can monitor their input directly on their output expose that through the
+/// play-through scope.
+fn add_play_through(obj: AudioDeviceID, opt: TraversalOptions) {
+    if !has_property_scoped(obj, kAudioDevicePropertyPlayThru, kAudioObjectPropertyScopePlayThrough)
+    {
+        return;
+    }
+    add_branch!("Play-through");
+    prop!(bool, PlayThrough, kAudioDevicePropertyPlayThru, obj, opt);
+    prop!(bool, PlayThrough, kAudioDevicePropertyPlayThruSolo, obj, opt);
+    prop!(bool, PlayThrough, kAudioDevicePropertyMute, obj, opt);
+    prop!(f32, PlayThrough, kAudioDevicePropertyPlayThruVolumeScalar, obj, opt);
+    prop!(f32, PlayThrough, kAudioDevicePropertyPlayThruVolumeDecibels, obj, opt);
+    prop!(f32, PlayThrough, kAudioDevicePropertyPlayThruStereoPan, obj, opt);
+    prop!(u32, PlayThrough, kAudioDevicePropertyPlayThruDestination, obj, opt, fourcc_to_string);
 }
 
 fn traverse_device(obj: AudioObjectID, opt: TraversalOptions) {
@@ -457,13 +1779,60 @@ fn traverse_device(obj: AudioObjectID, opt: TraversalOptions) {
     prop!(string, kAudioDevicePropertyModelUID, obj, opt);
     prop!(u32, kAudioDevicePropertyTransportType, obj, opt, transporttype_to_str);
     prop!(pid_t, kAudioDevicePropertyHogMode, obj, opt);
+    // This is the status of loading the device's IOAudio driver plug-in, not an object.
+    prop!(OSStatus, kAudioDevicePropertyPlugIn, obj, opt);
+    let plugin =
+        owning_plugin(obj).and_then(|p| get_string_property(p, kAudioPlugInPropertyBundleID));
+    if opt.contains(TraversalOptions::DEBUG) {
+        add_leaf!("Implemented by: {:?}", plugin);
+    } else if let Ok(bundle_id) = plugin {
+        add_leaf!("Implemented by: {}", bundle_id);
+    }
     prop!(Vec<AudioDeviceID>, kAudioDevicePropertyRelatedDevices, obj, opt);
     prop!(Vec<AudioDeviceID>, kAudioAggregateDevicePropertyActiveSubDeviceList, obj, opt);
     prop!(u32, kAudioDevicePropertyClockDomain, obj, opt);
     prop!(string, kAudioDevicePropertyClockDevice, obj, opt);
+    if opt.contains(TraversalOptions::INCLUDE_CLOCKS) {
+        if let Ok(clock) = get_string_property(obj, kAudioDevicePropertyClockDevice)
+            .and_then(|uid| clock_device_for_uid(&uid))
+        {
+            add_branch!("Clock device: {}", clock);
+            prop!(string, kAudioObjectPropertyName, clock, opt);
+            prop!(f64, kAudioClockDevicePropertyNominalSampleRate, clock, opt);
+            prop!(bool, kAudioClockDevicePropertyDeviceIsRunning, clock, opt);
+        }
+    }
     prop!(bool, kAudioDevicePropertyDeviceIsAlive, obj, opt);
-    prop!(bool, kAudioDevicePropertyDeviceIsRunningSomewhere, obj, opt);
-    prop!(bool, kAudioDevicePropertyDeviceIsRunning, obj, opt);
+    // IsRunning is about this process, IsRunningSomewhere about any process.
+    let here = get_property::<u32>(obj, kAudioDevicePropertyDeviceIsRunning);
+    let anywhere = get_property::<u32>(obj, kAudioDevicePropertyDeviceIsRunningSomewhere);
+    if let (Ok(here), Ok(anywhere)) = (here, anywhere) {
+        add_leaf!("Running: here={}, anywhere={}", here != 0, anywhere != 0);
+    } else {
+        prop!(bool, kAudioDevicePropertyDeviceIsRunning, obj, opt);
+        prop!(bool, kAudioDevicePropertyDeviceIsRunningSomewhere, obj, opt);
+    }
+    // Reading every process' devices for every device is costly, so only do it when processes
+    // are asked for.
+    let users = if opt.contains(TraversalOptions::INCLUDE_PROCESSES) {
+        processes_using_device(obj)
+    } else {
+        Vec::new()
+    };
+    if !users.is_empty() {
+        let names: Vec<&str> = users.iter().map(|(name, _)| name.as_str()).collect();
+        add_leaf!("In use by: {}", names.join(", "));
+    }
+    if anywhere.is_ok_and(|a| a != 0) {
+        let running: Vec<&str> = users
+            .iter()
+            .filter(|(_, running)| *running)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !running.is_empty() {
+            add_leaf!("Running for: {}", running.join(", "));
+        }
+    }
     prop!(bool, Input, kAudioDevicePropertyDeviceCanBeDefaultDevice, obj, opt);
     prop!(bool, Output, kAudioDevicePropertyDeviceCanBeDefaultDevice, obj, opt);
     prop!(bool, Output, kAudioDevicePropertyDeviceCanBeDefaultSystemDevice, obj, opt);
@@ -471,40 +1840,178 @@ fn traverse_device(obj: AudioObjectID, opt: TraversalOptions) {
     prop!(u32, Output, kAudioDevicePropertyLatency, obj, opt);
     prop!(Vec<AudioStreamID>, Input, kAudioDevicePropertyStreams, obj, opt);
     prop!(Vec<AudioStreamID>, Output, kAudioDevicePropertyStreams, obj, opt);
+    add_stream_configuration(obj, Scope::Input, opt);
+    add_stream_configuration(obj, Scope::Output, opt);
     prop!(Vec<AudioObjectID>, kAudioObjectPropertyControlList, obj, opt);
     prop!(u32, Input, kAudioDevicePropertySafetyOffset, obj, opt);
     prop!(u32, Output, kAudioDevicePropertySafetyOffset, obj, opt);
     prop!(f64, kAudioDevicePropertyActualSampleRate, obj, opt);
     prop!(f64, kAudioDevicePropertyNominalSampleRate, obj, opt);
     if opt.contains(TraversalOptions::INCLUDE_FORMATS) {
-        prop!(
-            Vec<AudioValueRange>,
-            Pretty,
-            kAudioDevicePropertyAvailableNominalSampleRates,
+        let rates = get_list_property::<AudioValueRange>(
             obj,
-            opt
+            kAudioDevicePropertyAvailableNominalSampleRates,
         );
+        if rates.as_ref().is_ok_and(|r| r.is_empty()) {
+            // Some aggregate devices have the property but report no rates at all.
+            add_leaf!("AvailableNominalSampleRates: (none reported)");
+            if opt.contains(TraversalOptions::DEBUG) {
+                let address = AudioObjectPropertyAddress {
+                    mSelector: kAudioDevicePropertyAvailableNominalSampleRates,
+                    mScope: kAudioObjectPropertyScopeGlobal,
+                    mElement: kAudioObjectPropertyElementMaster,
+                };
+                let mut size = 0;
+                let status = audio_object_get_property_data_size(obj, &address, &mut size);
+                add_leaf!("AvailableNominalSampleRates size: {} bytes (status {})", size, status);
+            }
+        } else {
+            prop!(
+                Vec<AudioValueRange>,
+                Pretty,
+                kAudioDevicePropertyAvailableNominalSampleRates,
+                obj,
+                opt
+            );
+        }
+        // Some interfaces support different rates for input and output, which the global read
+        // hides.
+        let global = rates.map(|r| rate_bounds(&r));
+        for scope in [Scope::Input, Scope::Output] {
+            if !scope_enabled(scope, opt) {
+                continue;
+            }
+            let scoped = get_list_property_scoped::<AudioValueRange>(
+                obj,
+                kAudioDevicePropertyAvailableNominalSampleRates,
+                scope.into(),
+            );
+            if let Ok(scoped) = scoped {
+                if global.as_ref().ok() != Some(&rate_bounds(&scoped)) {
+                    add_leaf!(
+                        "{} AvailableNominalSampleRates: {}",
+                        scope,
+                        wrap_lines(&format!("{:#?}", scoped))
+                    );
+                }
+            }
+        }
     }
     prop!(u32, kAudioDevicePropertyBufferFrameSize, obj, opt);
+    if let (Ok(frames), Ok(rate)) = (
+        get_property::<u32>(obj, kAudioDevicePropertyBufferFrameSize),
+        get_property::<f64>(obj, kAudioDevicePropertyNominalSampleRate),
+    ) {
+        if rate > 0.0 {
+            add_leaf!("BufferDuration: {:.2} ms", frames as f64 * 1000.0 / rate);
+        }
+    }
     prop!(AudioValueRange, kAudioDevicePropertyBufferFrameSizeRange, obj, opt);
+    prop!(u32, kAudioDevicePropertyZeroTimeStampPeriod, obj, opt);
+    if let (Ok(frames), Ok(rate)) = (
+        get_property::<u32>(obj, kAudioDevicePropertyZeroTimeStampPeriod),
+        get_property::<f64>(obj, kAudioDevicePropertyNominalSampleRate),
+    ) {
+        if rate > 0.0 {
+            add_leaf!("ZeroTimeStampPeriod duration: {:.2} ms", frames as f64 * 1000.0 / rate);
+        }
+    }
     prop!(u32, kAudioDevicePropertyUsesVariableBufferFrameSizes, obj, opt);
-    prop!(Vec<u32>, Input, kAudioDevicePropertyPreferredChannelsForStereo, obj, opt);
-    prop!(Vec<u32>, Output, kAudioDevicePropertyPreferredChannelsForStereo, obj, opt);
+    add_preferred_stereo(obj, Scope::Input, opt);
+    add_preferred_stereo(obj, Scope::Output, opt);
+    add_volume_decibels(obj, Scope::Input, opt);
+    add_volume_decibels(obj, Scope::Output, opt);
+    add_play_through(obj, opt);
     if opt.contains(TraversalOptions::INCLUDE_CHANNELS) {
-        prop!(
-            Vec<u8>,
-            Pretty,
-            Output,
-            kAudioDevicePropertyPreferredChannelLayout,
-            obj,
-            opt,
-            expand_channel_layout
-        );
+        add_element_names(obj, Scope::Input, opt);
+        add_element_names(obj, Scope::Output, opt);
+        let compact = if opt.contains(TraversalOptions::COMPACT_CHANNELS) {
+            get_channel_layout_scoped(
+                obj,
+                kAudioDevicePropertyPreferredChannelLayout,
+                kAudioObjectPropertyScopeOutput,
+            )
+            .ok()
+            .and_then(|layout| compact_channel_layout(&layout))
+        } else {
+            None
+        };
+        match compact {
+            Some(layout) if scope_enabled(Scope::Output, opt) => {
+                add_leaf!("Output Layout: {}", layout)
+            }
+            _ => {
+                prop!(ChannelLayout, Output, kAudioDevicePropertyPreferredChannelLayout, obj, opt);
+            }
+        }
     }
-    prop!(f32, kAudioDevicePropertyIOCycleUsage, obj, opt);
+    check_preferred_layout(obj, opt);
+    check_stream_rates(obj, opt);
+    prop!(f32, kAudioDevicePropertyIOCycleUsage, obj, opt, |p| {
+        Formatted(format!("{:.0}%", p * 100.0))
+    });
     prop!(u32, Input, kAudioDevicePropertyProcessMute, obj, opt);
 }
 
+fn traverse_selector_control(obj: AudioObjectID, opt: TraversalOptions) {
+    prop!(Vec<u32>, kAudioSelectorControlPropertyCurrentItem, obj, opt);
+    let current =
+        get_list_property::<u32>(obj, kAudioSelectorControlPropertyCurrentItem).unwrap_or_default();
+    let Ok(items) = get_list_property::<u32>(obj, kAudioSelectorControlPropertyAvailableItems)
+    else {
+        return;
+    };
+    add_branch!("AvailableItems");
+    for item in items {
+        let name =
+            get_string_property_with_qualifier(obj, kAudioSelectorControlPropertyItemName, &item);
+        let mut line = format!("{}: {:?}", fourcc_to_string(item), name);
+        // Clock source items report whether they are internal; most items have no kind.
+        if let Ok(kind) =
+            get_property_with_qualifier::<u32, _>(obj, kAudioSelectorControlPropertyItemKind, &item)
+        {
+            if kind != 0 {
+                line.push_str(&format!(" [kind {}]", fourcc_to_string(kind)));
+            }
+        }
+        if current.contains(&item) {
+            line.push_str(" [current]");
+        }
+        add_leaf!("{}", line);
+    }
+}
+
+fn traverse_transport_manager(obj: AudioObjectID, opt: TraversalOptions) {
+    prop!(Vec<AudioObjectID>, kAudioTransportManagerPropertyEndPointList, obj, opt);
+    prop!(u32, kAudioTransportManagerPropertyTransportType, obj, opt, transporttype_to_str);
+}
+
+fn traverse_clock(obj: AudioObjectID, opt: TraversalOptions) {
+    prop!(string, kAudioClockDevicePropertyDeviceUID, obj, opt);
+    prop!(u32, kAudioClockDevicePropertyTransportType, obj, opt, transporttype_to_str);
+    prop!(u32, kAudioClockDevicePropertyClockDomain, obj, opt);
+    prop!(bool, kAudioClockDevicePropertyDeviceIsAlive, obj, opt);
+    prop!(bool, kAudioClockDevicePropertyDeviceIsRunning, obj, opt);
+    prop!(u32, kAudioClockDevicePropertyLatency, obj, opt);
+    prop!(f64, kAudioClockDevicePropertyNominalSampleRate, obj, opt);
+    prop!(
+        Vec<AudioValueRange>,
+        Pretty,
+        kAudioClockDevicePropertyAvailableNominalSampleRates,
+        obj,
+        opt
+    );
+    prop!(Vec<AudioObjectID>, kAudioClockDevicePropertyControlList, obj, opt);
+    // Controls the clock device owns are traversed with its other owned objects.
+    let controls = get_list_property::<AudioObjectID>(obj, kAudioClockDevicePropertyControlList)
+        .unwrap_or_default();
+    for control in controls {
+        if get_property::<AudioObjectID>(control, kAudioObjectPropertyOwner) != Ok(obj) {
+            traverse_obj(control, opt);
+        }
+    }
+}
+
 fn terminaltype_to_str(t: u32) -> String {
     #[allow(non_upper_case_globals, non_snake_case)]
     match t {
@@ -536,77 +2043,309 @@ fn traverse_stream(obj: AudioStreamID, opt: TraversalOptions) {
     prop!(u32, kAudioStreamPropertyStartingChannel, obj, opt);
     prop!(u32, Input, kAudioStreamPropertyLatency, obj, opt);
     prop!(u32, Output, kAudioStreamPropertyLatency, obj, opt);
-    prop!(AudioStreamBasicDescription, Pretty, kAudioStreamPropertyVirtualFormat, obj, opt);
+    prop!(
+        AudioStreamBasicDescription,
+        Pretty,
+        kAudioStreamPropertyVirtualFormat,
+        obj,
+        opt,
+        normalize_asbd
+    );
     if opt.contains(TraversalOptions::INCLUDE_FORMATS) {
         prop!(
             Vec<AudioStreamRangedDescription>,
             Pretty,
             kAudioStreamPropertyAvailableVirtualFormats,
             obj,
-            opt
+            opt,
+            normalize_ranged_asbds
         );
+    } else if let Ok(n) = get_list_property_len::<AudioStreamRangedDescription>(
+        obj,
+        kAudioStreamPropertyAvailableVirtualFormats,
+    ) {
+        add_leaf!("{} available virtual formats", n);
+    }
+    prop!(
+        AudioStreamBasicDescription,
+        Pretty,
+        kAudioStreamPropertyPhysicalFormat,
+        obj,
+        opt,
+        normalize_asbd
+    );
+    if let Ok(asbd) =
+        get_property::<AudioStreamBasicDescription>(obj, kAudioStreamPropertyPhysicalFormat)
+    {
+        add_leaf!("Physical format: {}", asbd_summary(asbd));
     }
-    prop!(AudioStreamBasicDescription, Pretty, kAudioStreamPropertyPhysicalFormat, obj, opt);
     if opt.contains(TraversalOptions::INCLUDE_FORMATS) {
         prop!(
             Vec<AudioStreamRangedDescription>,
             Pretty,
             kAudioStreamPropertyAvailablePhysicalFormats,
             obj,
-            opt
+            opt,
+            normalize_ranged_asbds
         );
+    } else if let Ok(n) = get_list_property_len::<AudioStreamRangedDescription>(
+        obj,
+        kAudioStreamPropertyAvailablePhysicalFormats,
+    ) {
+        add_leaf!("{} available physical formats", n);
     }
 }
 
+/// Returns the bundle IDs, or pids if they have none, of the processes that have `obj` open for
+/// input or output, each with whether it is running IO in a scope it has `obj` open in.
+fn processes_using_device(obj: AudioDeviceID) -> Vec<(String, bool)> {
+    let processes = get_list_property::<AudioObjectID>(
+        kAudioObjectSystemObject,
+        kAudioHardwarePropertyProcessObjectList,
+    )
+    .unwrap_or_default();
+    processes
+        .into_iter()
+        .filter_map(|process| {
+            let mut uses = false;
+            let mut running = false;
+            for (scope, is_running) in [
+                (kAudioObjectPropertyScopeInput, kAudioProcessPropertyIsRunningInput),
+                (kAudioObjectPropertyScopeOutput, kAudioProcessPropertyIsRunningOutput),
+            ] {
+                if get_list_property_scoped::<AudioObjectID>(
+                    process,
+                    kAudioProcessPropertyDevices,
+                    scope,
+                )
+                .is_ok_and(|devices| devices.contains(&obj))
+                {
+                    uses = true;
+                    running |= get_property::<u32>(process, is_running).is_ok_and(|r| r != 0);
+                }
+            }
+            uses.then_some((process, running))
+        })
+        .map(|(process, running)| {
+            let name = get_string_property(process, kAudioProcessPropertyBundleID)
+                .ok()
+                .filter(|id| !id.is_empty())
+                .or_else(|| {
+                    get_property::<pid_t>(process, kAudioProcessPropertyPID)
+                        .ok()
+                        .map(|pid| format!("pid {}", pid))
+                })
+                .unwrap_or_else(|| format!("AudioObjectID {}", process));
+            (name, running)
+        })
+        .collect()
+}
+
+/// Renders objects by name, with their IDs, like "Built-in Microphone (62), BlackHole (74)".
+fn object_names(objects: Vec<AudioObjectID>) -> String {
+    objects
+        .into_iter()
+        .map(|obj| format!("{} ({})", object_name(obj), obj))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn traverse_process(obj: AudioObjectID, opt: TraversalOptions) {
     prop!(pid_t, kAudioProcessPropertyPID, obj, opt);
     prop!(string, kAudioProcessPropertyBundleID, obj, opt);
-    prop!(Vec<AudioObjectID>, Input, kAudioProcessPropertyDevices, obj, opt);
-    prop!(Vec<AudioObjectID>, Output, kAudioProcessPropertyDevices, obj, opt);
+    prop!(Vec<AudioObjectID>, Input, kAudioProcessPropertyDevices, obj, opt, object_names);
+    prop!(Vec<AudioObjectID>, Output, kAudioProcessPropertyDevices, obj, opt, object_names);
     prop!(bool, kAudioProcessPropertyIsRunning, obj, opt);
     prop!(bool, kAudioProcessPropertyIsRunningInput, obj, opt);
     prop!(bool, kAudioProcessPropertyIsRunningOutput, obj, opt);
 }
 
+thread_local! {
+    /// The default input, output and system output devices, as read by the latest `traverse_hw`.
+    static DEFAULT_DEVICES: Cell<[AudioObjectID; 3]> =
+        const { Cell::new([kAudioObjectUnknown; 3]) };
+}
+
+/// Reads the default input, output and system output devices, remembering them for
+/// `header_annotations`.
+fn read_default_devices() -> [AudioObjectID; 3] {
+    let devices = [
+        kAudioHardwarePropertyDefaultInputDevice,
+        kAudioHardwarePropertyDefaultOutputDevice,
+        kAudioHardwarePropertyDefaultSystemOutputDevice,
+    ]
+    .map(|selector| {
+        get_property::<AudioObjectID>(kAudioObjectSystemObject, selector)
+            .unwrap_or(kAudioObjectUnknown)
+    });
+    DEFAULT_DEVICES.with(|defaults| defaults.set(devices));
+    devices
+}
+
+/// Groups devices by `kAudioDevicePropertyModelUID`, keeping only models with more than one
+/// device, e.g. the speaker and headphone endpoints of the same physical device.
+pub fn model_groups() -> BTreeMap<String, Vec<AudioDeviceID>> {
+    let mut groups: BTreeMap<String, Vec<AudioDeviceID>> = BTreeMap::new();
+    let devices =
+        get_list_property::<AudioDeviceID>(kAudioObjectSystemObject, kAudioHardwarePropertyDevices)
+            .unwrap_or_default();
+    for device in devices {
+        if let Ok(model) = get_string_property(device, kAudioDevicePropertyModelUID) {
+            if !model.is_empty() {
+                groups.entry(model).or_default().push(device);
+            }
+        }
+    }
+    groups.retain(|_, devices| devices.len() > 1);
+    groups
+}
+
 fn traverse_hw(obj: AudioObjectID, opt: TraversalOptions) {
-    prop!(Vec<AudioObjectID>, kAudioHardwarePropertyDevices, obj, opt);
+    read_default_devices();
+    prop!(Vec<AudioObjectID>, kAudioHardwarePropertyDevices, obj, opt, |mut devices| {
+        if opt.contains(TraversalOptions::SORTED) {
+            sort_objects(&mut devices);
+        }
+        devices
+    });
     prop!(AudioObjectID, kAudioHardwarePropertyDefaultInputDevice, obj, opt);
     prop!(AudioObjectID, kAudioHardwarePropertyDefaultOutputDevice, obj, opt);
     prop!(AudioObjectID, kAudioHardwarePropertyDefaultSystemOutputDevice, obj, opt);
-    prop!(bool, kAudioHardwarePropertyMixStereoToMono, obj, opt);
+    let groups = model_groups();
+    if !groups.is_empty() {
+        add_branch!("Models");
+        for (model, devices) in groups {
+            let names: Vec<String> = devices.into_iter().map(object_name).collect();
+            add_leaf!("Model {}: {}", model, names.join(", "));
+        }
+    }
     prop!(Vec<AudioObjectID>, kAudioHardwarePropertyPlugInList, obj, opt);
     prop!(Vec<AudioObjectID>, kAudioHardwarePropertyTransportManagerList, obj, opt);
     prop!(Vec<AudioObjectID>, kAudioHardwarePropertyBoxList, obj, opt);
     prop!(Vec<AudioObjectID>, kAudioHardwarePropertyClockDeviceList, obj, opt);
-    prop!(bool, kAudioHardwarePropertyProcessIsMain, obj, opt);
     prop!(bool, kAudioHardwarePropertyIsInitingOrExiting, obj, opt);
-    prop!(bool, kAudioHardwarePropertyProcessInputMute, obj, opt);
-    prop!(bool, kAudioHardwarePropertyProcessIsAudible, obj, opt);
-    prop!(bool, kAudioHardwarePropertySleepingIsAllowed, obj, opt);
-    prop!(bool, kAudioHardwarePropertyUnloadingIsAllowed, obj, opt);
-    prop!(bool, kAudioHardwarePropertyHogModeIsAllowed, obj, opt);
-    prop!(bool, kAudioHardwarePropertyUserSessionIsActiveOrHeadless, obj, opt);
+    {
+        add_branch!("System toggles");
+        prop!(bool, kAudioHardwarePropertyProcessIsMain, obj, opt);
+        prop!(bool, kAudioHardwarePropertyProcessInputMute, obj, opt);
+        prop!(bool, kAudioHardwarePropertyProcessIsAudible, obj, opt);
+        prop!(bool, kAudioHardwarePropertySleepingIsAllowed, obj, opt);
+        prop!(bool, kAudioHardwarePropertyUnloadingIsAllowed, obj, opt);
+        prop!(bool, kAudioHardwarePropertyHogModeIsAllowed, obj, opt);
+        prop!(bool, kAudioHardwarePropertyUserSessionIsActiveOrHeadless, obj, opt);
+        prop!(bool, kAudioHardwarePropertyMixStereoToMono, obj, opt);
+    }
     prop!(AudioHardwarePowerHint, kAudioHardwarePropertyPowerHint, obj, opt);
     prop!(Vec<AudioObjectID>, kAudioHardwarePropertyProcessObjectList, obj, opt);
     prop!(Vec<AudioObjectID>, kAudioHardwarePropertyTapList, obj, opt);
+    if opt.intersects(TraversalOptions::INCLUDE_TAPS | TraversalOptions::INCLUDE_PROCESSES) {
+        // Taps the system object owns are traversed with its other owned objects.
+        let owned = get_list_property::<AudioObjectID>(obj, kAudioObjectPropertyOwnedObjects)
+            .unwrap_or_default();
+        let taps: Vec<AudioObjectID> =
+            get_list_property::<AudioObjectID>(obj, kAudioHardwarePropertyTapList)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|tap| !owned.contains(tap))
+                .collect();
+        if !taps.is_empty() {
+            add_branch!("Taps");
+            for tap in taps {
+                traverse_child(tap, opt);
+            }
+        }
+    }
+}
+
+/// Sorts objects by UID, then name, then ID, so that repeated captures are stable regardless of
+/// the order the HAL returns them in.
+fn sort_objects(objects: &mut [AudioObjectID]) {
+    objects.sort_by_cached_key(|&obj| {
+        (
+            get_string_property(obj, kAudioDevicePropertyDeviceUID).ok(),
+            get_string_property(obj, kAudioObjectPropertyName).ok(),
+            obj,
+        )
+    });
+}
+
+const CONTROL_BASE_CLASS_IDS: [AudioClassID; 6] = [
+    kAudioControlClassID,
+    kAudioSliderControlClassID,
+    kAudioLevelControlClassID,
+    kAudioBooleanControlClassID,
+    kAudioSelectorControlClassID,
+    kAudioStereoPanControlClassID,
+];
+
+fn header_annotations(
+    obj: AudioObjectID,
+    base_class_id: Result<AudioClassID, OSStatus>,
+    class_id: Result<AudioClassID, OSStatus>,
+) -> Vec<String> {
+    let mut annotations = Vec::new();
+    if base_class_id.is_ok_and(|id| CONTROL_BASE_CLASS_IDS.contains(&id)) {
+        if let (Ok(scope), Ok(element)) = (
+            get_property::<u32>(obj, kAudioControlPropertyScope),
+            get_property::<u32>(obj, kAudioControlPropertyElement),
+        ) {
+            annotations.push(format!("{}, element {}", scope_to_str(scope), element));
+        }
+    }
+    if class_id.is_ok_and(|id| id == kAudioStreamClassID) {
+        // Name the owning device, as a stream's header can be far from its device's.
+        if let Some(&device) = owner_chain(obj).first() {
+            annotations.push(object_name(device));
+        }
+        if get_property::<u32>(obj, kAudioStreamPropertyIsActive).is_ok_and(|p| p != 0) {
+            annotations.push("ACTIVE".to_string());
+        }
+    }
+    let [input, output, system] = DEFAULT_DEVICES.with(Cell::get);
+    for (id, annotation) in [
+        (input, "DEFAULT INPUT"),
+        (output, "DEFAULT OUTPUT"),
+        (system, "DEFAULT SYSTEM"),
+    ] {
+        if obj == id {
+            annotations.push(annotation.to_string());
+        }
+    }
+    annotations
+}
+
+/// Virtual devices often leave their manufacturer empty, while the plug-in implementing them has
+/// one.
+fn add_plugin_manufacturer(obj: AudioDeviceID) {
+    if get_string_property(obj, kAudioObjectPropertyManufacturer).is_ok_and(|m| !m.is_empty()) {
+        return;
+    }
+    if let Ok(manufacturer) = owning_plugin(obj)
+        .and_then(|plugin| get_string_property(plugin, kAudioObjectPropertyManufacturer))
+    {
+        if !manufacturer.is_empty() {
+            add_leaf!("Manufacturer (via plug-in): {}", manufacturer);
+        }
+    }
 }
 
 fn traverse_obj(obj: AudioObjectID, opt: TraversalOptions) {
     let owned_objects = get_list_property::<AudioObjectID>(obj, kAudioObjectPropertyOwnedObjects);
     let base_class_id = get_property::<AudioClassID>(obj, kAudioObjectPropertyBaseClass);
     let class_id = get_property::<AudioClassID>(obj, kAudioObjectPropertyClass);
+    // A device can be unplugged between its owner listing it and us reading it. Don't flood the
+    // tree with errors for the whole subtree.
+    let vanished = Err(kAudioHardwareBadObjectError as OSStatus);
+    if class_id == vanished || base_class_id == vanished {
+        add_leaf!("(object {} vanished during traversal)", obj);
+        return;
+    }
+    let _path = PathGuard::push(obj, class_id);
+    record_error(&owned_objects);
+    record_error(&base_class_id);
+    record_error(&class_id);
     if !opt.contains(TraversalOptions::INCLUDE_CONTROLS)
-        && base_class_id.is_ok_and(|id| {
-            [
-                kAudioControlClassID,
-                kAudioSliderControlClassID,
-                kAudioLevelControlClassID,
-                kAudioBooleanControlClassID,
-                kAudioSelectorControlClassID,
-                kAudioStereoPanControlClassID,
-            ]
-            .contains(&id)
-        })
+        && base_class_id.is_ok_and(|id| CONTROL_BASE_CLASS_IDS.contains(&id))
     {
         return;
     }
@@ -625,6 +2364,16 @@ fn traverse_obj(obj: AudioObjectID, opt: TraversalOptions) {
     {
         return;
     }
+    if class_id.is_ok_and(|id| id == kAudioStreamClassID) {
+        let scope = match get_property::<u32>(obj, kAudioStreamPropertyDirection) {
+            Ok(1) => Scope::Input,
+            Ok(_) => Scope::Output,
+            Err(_) => Scope::Global,
+        };
+        if !scope_enabled(scope, opt) {
+            return;
+        }
+    }
     if !opt.contains(TraversalOptions::INCLUDE_PLUGINS)
         && class_id.is_ok_and(|id| id == kAudioPlugInClassID)
     {
@@ -635,16 +2384,33 @@ fn traverse_obj(obj: AudioObjectID, opt: TraversalOptions) {
     {
         return;
     }
-    add_branch!("AudioObjectID: {}", obj);
+    let mut header = format!("AudioObjectID: {}", obj);
+    for annotation in header_annotations(obj, base_class_id, class_id) {
+        header.push_str(&format!(" [{}]", annotation));
+    }
+    add_branch!("{}", header);
     add_class_id("BaseClass", base_class_id);
     add_class_id("Class", class_id);
-    prop!(bool, kAudioObjectPropertyOwner, obj, opt);
+    prop!(AudioObjectID, kAudioObjectPropertyOwner, obj, opt);
+    if opt.contains(TraversalOptions::SHOW_OWNERS) {
+        let owners: Vec<String> = owner_chain(obj)
+            .into_iter()
+            .map(|o| format!("{} ({})", object_name(o), o))
+            .collect();
+        add_leaf!("Owner chain: {}", owners.join(" -> "));
+    }
     prop!(string, kAudioObjectPropertyName, obj, opt);
     prop!(string, kAudioObjectPropertyModelName, obj, opt);
     prop!(string, kAudioObjectPropertyManufacturer, obj, opt);
+    if class_id == Ok(kAudioDeviceClassID) || base_class_id == Ok(kAudioDeviceClassID) {
+        add_plugin_manufacturer(obj);
+    }
     prop!(string, kAudioObjectPropertyElementName, obj, opt);
     prop!(string, kAudioObjectPropertyElementNumberName, obj, opt);
     prop!(string, kAudioDevicePropertyDeviceUID, obj, opt);
+    if opt.contains(TraversalOptions::INCLUDE_CUSTOM_PROPERTIES) {
+        traverse_custom_properties(obj, opt);
+    }
     #[allow(non_upper_case_globals, non_snake_case)]
     match class_id {
         Ok(kAudioSystemObjectClassID) => traverse_hw(obj, opt),
@@ -652,16 +2418,270 @@ fn traverse_obj(obj: AudioObjectID, opt: TraversalOptions) {
             traverse_aggregate_device(obj, opt);
             traverse_device(obj, opt);
         }
-        Ok(kAudioSubDeviceClassID) | Ok(kAudioDeviceClassID) => traverse_device(obj, opt),
+        Ok(kAudioSubDeviceClassID) => {
+            traverse_subdevice(obj, opt);
+            traverse_device(obj, opt);
+        }
+        Ok(kAudioEndPointDeviceClassID) => {
+            traverse_endpoint_device(obj, opt);
+            traverse_device(obj, opt);
+        }
+        Ok(kAudioDeviceClassID) => traverse_device(obj, opt),
+        Ok(kAudioClockDeviceClassID) => traverse_clock(obj, opt),
+        Ok(kAudioTransportManagerClassID) => traverse_transport_manager(obj, opt),
         Ok(kAudioStreamClassID) => traverse_stream(obj, opt),
         Ok(kAudioProcessClassID) => traverse_process(obj, opt),
+        Ok(kAudioTapClassID) | Ok(kAudioSubTapClassID) => traverse_tap(obj, opt),
+        _ if base_class_id == Ok(kAudioSelectorControlClassID) => {
+            traverse_selector_control(obj, opt)
+        }
+        _ => {}
+    }
+    if let Ok(mut objects) = owned_objects {
+        if class_id == Ok(kAudioAggregateDeviceClassID) {
+            // traverse_aggregate_device already listed these under "Sub-taps".
+            objects.retain(|&o| {
+                get_property::<AudioClassID>(o, kAudioObjectPropertyClass)
+                    != Ok(kAudioSubTapClassID)
+            });
+        }
+        if opt.contains(TraversalOptions::SORTED) {
+            sort_objects(&mut objects);
+        }
+        if opt.contains(TraversalOptions::PARALLEL) && class_id == Ok(kAudioSystemObjectClassID) {
+            traverse_children_parallel(&objects, opt);
+            return;
+        }
+        for obj in objects {
+            traverse_child(obj, opt);
+        }
+    }
+}
+
+fn traverse_child(obj: AudioObjectID, opt: TraversalOptions) {
+    if opt.contains(TraversalOptions::PROPAGATE_PANICS) {
+        traverse_obj(obj, opt);
+    } else if std::panic::catch_unwind(|| traverse_obj(obj, opt)).is_err() {
+        // The panic hook has already reported where it happened.
+        CURRENT_PROPERTY.with(|p| p.set(None));
+        add_leaf!("(panicked reading object {}, skipping)", obj);
+    }
+}
+
+/// Traverses `objects` on a bounded pool of threads, then replays what each traversal emitted into
+/// the current sink in the order of `objects`, so the output doesn't depend on which thread
+/// finished first.
+fn traverse_children_parallel(objects: &[AudioObjectID], opt: TraversalOptions) {
+    let threads = std::thread::available_parallelism()
+        .map_or(4, usize::from)
+        .min(objects.len());
+    let defaults = DEFAULT_DEVICES.with(Cell::get);
+    let path = TRAVERSAL_PATH.with(|path| path.borrow().clone());
+    let next = AtomicUsize::new(0);
+    let results: Vec<Mutex<Option<(RecordingSink, usize)>>> =
+        objects.iter().map(|_| Mutex::new(None)).collect();
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                // Give the worker the same context a serial traversal would have had here.
+                DEFAULT_DEVICES.with(|d| d.set(defaults));
+                TRAVERSAL_PATH.with(|p| *p.borrow_mut() = path.clone());
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&obj) = objects.get(i) else {
+                        break;
+                    };
+                    let errors = traversal_errors();
+                    let recording =
+                        with_sink(RecordingSink::default(), || traverse_child(obj, opt));
+                    *results[i].lock().unwrap() = Some((recording, traversal_errors() - errors));
+                }
+            });
+        }
+    });
+    for result in results {
+        // A worker only leaves a result empty if it panicked under PROPAGATE_PANICS, and then
+        // the scope has already propagated the panic.
+        let (recording, errors) = result.into_inner().unwrap().unwrap();
+        TRAVERSAL_ERRORS.with(|e| e.set(e.get() + errors));
+        recording.replay();
+    }
+}
+
+/// Counts of the objects found when walking the whole object tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub objects: usize,
+    pub devices: usize,
+    pub input_devices: usize,
+    pub output_devices: usize,
+    pub streams: usize,
+    pub controls: usize,
+    pub processes: usize,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} devices ({} input, {} output), {} processes, {} streams, {} controls, {} objects in total",
+            self.devices,
+            self.input_devices,
+            self.output_devices,
+            self.processes,
+            self.streams,
+            self.controls,
+            self.objects
+        )
+    }
+}
+
+fn summarize_obj(obj: AudioObjectID, summary: &mut Summary) {
+    summary.objects += 1;
+    let base_class_id = get_property::<AudioClassID>(obj, kAudioObjectPropertyBaseClass);
+    let class_id = get_property::<AudioClassID>(obj, kAudioObjectPropertyClass);
+    #[allow(non_upper_case_globals, non_snake_case)]
+    match class_id {
+        Ok(kAudioDeviceClassID) | Ok(kAudioAggregateDeviceClassID) => {
+            summary.devices += 1;
+            if channel_count(obj, Scope::Input).is_ok_and(|c| c > 0) {
+                summary.input_devices += 1;
+            }
+            if channel_count(obj, Scope::Output).is_ok_and(|c| c > 0) {
+                summary.output_devices += 1;
+            }
+        }
+        Ok(kAudioStreamClassID) => summary.streams += 1,
+        Ok(kAudioProcessClassID) => summary.processes += 1,
+        _ if base_class_id.is_ok_and(|id| CONTROL_BASE_CLASS_IDS.contains(&id)) => {
+            summary.controls += 1
+        }
         _ => {}
     }
-    if let Ok(objects) = owned_objects {
+    if let Ok(objects) = get_list_property::<AudioObjectID>(obj, kAudioObjectPropertyOwnedObjects) {
         for obj in objects {
-            traverse_obj(obj, opt);
+            summarize_obj(obj, summary);
+        }
+    }
+}
+
+/// Walks the whole object tree, counting objects per kind instead of printing them.
+pub fn summarize() -> Summary {
+    let mut summary = Summary::default();
+    summarize_obj(kAudioObjectSystemObject, &mut summary);
+    summary
+}
+
+/// The most commonly needed facts about a device, read in one go.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeviceInfo {
+    pub id: AudioDeviceID,
+    pub uid: Option<String>,
+    pub name: Option<String>,
+    pub manufacturer: Option<String>,
+    pub transport: Option<&'static str>,
+    pub transport_category: Option<TransportCategory>,
+    pub input_channels: u32,
+    pub output_channels: u32,
+    pub nominal_rate: Option<f64>,
+    pub is_default_input: bool,
+    pub is_default_output: bool,
+}
+
+pub fn device_info(obj: AudioDeviceID) -> DeviceInfo {
+    let is_default =
+        |selector| get_property::<AudioObjectID>(kAudioObjectSystemObject, selector) == Ok(obj);
+    let transport = get_property::<u32>(obj, kAudioDevicePropertyTransportType).ok();
+    DeviceInfo {
+        id: obj,
+        uid: get_string_property(obj, kAudioDevicePropertyDeviceUID).ok(),
+        name: get_string_property(obj, kAudioObjectPropertyName).ok(),
+        manufacturer: get_string_property(obj, kAudioObjectPropertyManufacturer).ok(),
+        transport: transport.map(transporttype_to_str),
+        transport_category: transport.map(transport_category),
+        input_channels: channel_count(obj, Scope::Input).unwrap_or(0),
+        output_channels: channel_count(obj, Scope::Output).unwrap_or(0),
+        nominal_rate: get_property::<f64>(obj, kAudioDevicePropertyNominalSampleRate).ok(),
+        is_default_input: is_default(kAudioHardwarePropertyDefaultInputDevice),
+        is_default_output: is_default(kAudioHardwarePropertyDefaultOutputDevice),
+    }
+}
+
+/// Returns a `DeviceInfo` for each device in `kAudioHardwarePropertyDevices`.
+pub fn all_device_infos() -> Vec<DeviceInfo> {
+    get_list_property::<AudioDeviceID>(kAudioObjectSystemObject, kAudioHardwarePropertyDevices)
+        .unwrap_or_default()
+        .into_iter()
+        .map(device_info)
+        .collect()
+}
+
+extern "C" {
+    fn sysctlbyname(
+        name: *const c_char,
+        oldp: *mut c_void,
+        oldlenp: *mut usize,
+        newp: *mut c_void,
+        newlen: usize,
+    ) -> i32;
+}
+
+/// Reads a string sysctl, like "hw.model".
+fn sysctl_string(name: &CStr) -> Option<String> {
+    let mut size = 0;
+    if unsafe { sysctlbyname(name.as_ptr(), ptr::null_mut(), &mut size, ptr::null_mut(), 0) } != 0 {
+        return None;
+    }
+    let mut buffer = vec![0u8; size];
+    let status = unsafe {
+        sysctlbyname(
+            name.as_ptr(),
+            buffer.as_mut_ptr() as *mut c_void,
+            &mut size,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if status != 0 {
+        return None;
+    }
+    buffer.truncate(size);
+    let value = CStr::from_bytes_until_nul(&buffer).ok()?;
+    Some(value.to_string_lossy().into_owned())
+}
+
+/// Describes the OS and machine, like "macOS 14.5 (23F79), MacBookPro18,3", so a capture keeps
+/// its context when attached to a bug report.
+pub fn system_header() -> String {
+    let unknown = || "unknown".to_string();
+    let version = sysctl_string(c"kern.osproductversion").unwrap_or_else(unknown);
+    let model = sysctl_string(c"hw.model").unwrap_or_else(unknown);
+    match sysctl_string(c"kern.osversion") {
+        Some(build) => format!("macOS {} ({}), {}", version, build, model),
+        None => format!("macOS {}, {}", version, model),
+    }
+}
+
+/// Prints system-wide context, including this process' own process object, for bug reports.
+pub fn system_summary(opt: TraversalOptions) {
+    let obj = kAudioObjectSystemObject;
+    {
+        add_branch!("System");
+        prop!(bool, kAudioHardwarePropertyProcessIsMain, obj, opt);
+        match process_object_for_pid(std::process::id() as pid_t) {
+            Ok(process) => {
+                add_leaf!("ProcessObject: {}", process);
+                prop!(string, kAudioProcessPropertyBundleID, process, opt);
+            }
+            Err(e) => add_leaf!("ProcessObject: Err({})", e),
         }
+        prop!(bool, kAudioHardwarePropertyMixStereoToMono, obj, opt);
+        prop!(bool, kAudioHardwarePropertySleepingIsAllowed, obj, opt);
+        prop!(bool, kAudioHardwarePropertyUnloadingIsAllowed, obj, opt);
+        prop!(bool, kAudioHardwarePropertyHogModeIsAllowed, obj, opt);
+        prop!(AudioHardwarePowerHint, kAudioHardwarePropertyPowerHint, obj, opt);
     }
+    default_tree().flush_print();
 }
 
 pub fn traverse() {
@@ -674,9 +2694,66 @@ pub fn traverse_with_options(opt: TraversalOptions) {
     default_tree().flush_print();
 }
 
+/// Traverses the whole object tree into `sink` instead of printing it, and hands `sink` back.
+pub fn traverse_with_sink<S: PropertySink + 'static>(sink: S, opt: TraversalOptions) -> S {
+    with_sink(sink, || traverse_obj(kAudioObjectSystemObject, opt))
+}
+
+/// Traverses the whole object tree into an `AudioObjectNode` tree, rooted at the system object.
+pub fn traverse_to_node(opt: TraversalOptions) -> AudioObjectNode {
+    let mut root = traverse_with_sink(NodeSink::default(), opt).into_root();
+    match root.entries.pop() {
+        Some(NodeEntry::Child(system)) => system,
+        _ => AudioObjectNode::default(),
+    }
+}
+
+/// Traverses only `obj` and the objects it owns.
+pub fn traverse_object(obj: AudioObjectID, opt: TraversalOptions) {
+    read_default_devices();
+    traverse_obj(obj, opt);
+    default_tree().flush_print();
+}
+
+/// Traverses the process object of `pid`, then the devices it uses for input and output with their
+/// streams.
+pub fn traverse_pid(pid: pid_t, opt: TraversalOptions) -> Result<(), OSStatus> {
+    let process = process_object_for_pid(pid)?;
+    traverse_object(process, opt | TraversalOptions::INCLUDE_PROCESSES);
+    let mut traversed = HashSet::new();
+    for scope in [
+        kAudioObjectPropertyScopeInput,
+        kAudioObjectPropertyScopeOutput,
+    ] {
+        let devices =
+            get_list_property_scoped::<AudioObjectID>(process, kAudioProcessPropertyDevices, scope)
+                .unwrap_or_default();
+        for device in devices {
+            if traversed.insert(device) {
+                traverse_object(device, opt | TraversalOptions::INCLUDE_STREAMS);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Traverses the default input, output and system output devices, each once, with their streams
+/// and controls.
+pub fn traverse_default_devices(opt: TraversalOptions) {
+    let mut traversed = HashSet::new();
+    for device in read_default_devices() {
+        if device != kAudioObjectUnknown && traversed.insert(device) {
+            traverse_object(
+                device,
+                opt | TraversalOptions::INCLUDE_STREAMS | TraversalOptions::INCLUDE_CONTROLS,
+            );
+        }
+    }
+}
+
 bitflags::bitflags! {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-    pub struct TraversalOptions: u16 {
+    pub struct TraversalOptions: u32 {
         const INCLUDE_BOXES = 1 << 0;
         const INCLUDE_CLOCKS = 1 << 1;
         const INCLUDE_STREAMS = 1 << 2;
@@ -686,5 +2763,274 @@ bitflags::bitflags! {
         const INCLUDE_PLUGINS = 1 << 6;
         const INCLUDE_PROCESSES = 1 << 7;
         const DEBUG = 1 << 8;
+        const SHOW_OWNERS = 1 << 9;
+        const INPUT_ONLY = 1 << 10;
+        const OUTPUT_ONLY = 1 << 11;
+        const INCLUDE_CUSTOM_PROPERTIES = 1 << 12;
+        const PROPAGATE_PANICS = 1 << 13;
+        const SORTED = 1 << 14;
+        const COMPACT_CHANNELS = 1 << 15;
+        const PARALLEL = 1 << 16;
+        const HEXDUMP = 1 << 17;
+        const INCLUDE_TAPS = 1 << 18;
+        const HIDE_EMPTY = 1 << 19;
+        const INCLUDE_ALL = Self::INCLUDE_BOXES.bits()
+            | Self::INCLUDE_CLOCKS.bits()
+            | Self::INCLUDE_STREAMS.bits()
+            | Self::INCLUDE_FORMATS.bits()
+            | Self::INCLUDE_CHANNELS.bits()
+            | Self::INCLUDE_CONTROLS.bits()
+            | Self::INCLUDE_PLUGINS.bits()
+            | Self::INCLUDE_PROCESSES.bits()
+            | Self::INCLUDE_CUSTOM_PROPERTIES.bits()
+            | Self::INCLUDE_TAPS.bits();
+    }
+}
+
+impl TraversalOptions {
+    pub fn builder() -> TraversalOptionsBuilder {
+        TraversalOptionsBuilder(TraversalOptions::empty())
+    }
+}
+
+/// Chainable alternative to OR-ing `TraversalOptions` flags together, e.g.
+/// `TraversalOptions::builder().streams().formats().debug().build()`.
+#[derive(Clone, Copy, Debug)]
+pub struct TraversalOptionsBuilder(TraversalOptions);
+
+impl TraversalOptionsBuilder {
+    fn with(mut self, flag: TraversalOptions) -> Self {
+        self.0.insert(flag);
+        self
+    }
+
+    pub fn boxes(self) -> Self {
+        self.with(TraversalOptions::INCLUDE_BOXES)
+    }
+
+    pub fn clocks(self) -> Self {
+        self.with(TraversalOptions::INCLUDE_CLOCKS)
+    }
+
+    pub fn streams(self) -> Self {
+        self.with(TraversalOptions::INCLUDE_STREAMS)
+    }
+
+    pub fn formats(self) -> Self {
+        self.with(TraversalOptions::INCLUDE_FORMATS)
+    }
+
+    pub fn channels(self) -> Self {
+        self.with(TraversalOptions::INCLUDE_CHANNELS)
+    }
+
+    pub fn controls(self) -> Self {
+        self.with(TraversalOptions::INCLUDE_CONTROLS)
+    }
+
+    pub fn plugins(self) -> Self {
+        self.with(TraversalOptions::INCLUDE_PLUGINS)
+    }
+
+    pub fn processes(self) -> Self {
+        self.with(TraversalOptions::INCLUDE_PROCESSES)
+    }
+
+    pub fn custom_properties(self) -> Self {
+        self.with(TraversalOptions::INCLUDE_CUSTOM_PROPERTIES)
+    }
+
+    pub fn taps(self) -> Self {
+        self.with(TraversalOptions::INCLUDE_TAPS)
+    }
+
+    pub fn all(self) -> Self {
+        self.with(TraversalOptions::INCLUDE_ALL)
+    }
+
+    pub fn debug(self) -> Self {
+        self.with(TraversalOptions::DEBUG)
+    }
+
+    pub fn show_owners(self) -> Self {
+        self.with(TraversalOptions::SHOW_OWNERS)
+    }
+
+    pub fn input_only(self) -> Self {
+        self.with(TraversalOptions::INPUT_ONLY)
+    }
+
+    pub fn output_only(self) -> Self {
+        self.with(TraversalOptions::OUTPUT_ONLY)
+    }
+
+    pub fn propagate_panics(self) -> Self {
+        self.with(TraversalOptions::PROPAGATE_PANICS)
+    }
+
+    pub fn sorted(self) -> Self {
+        self.with(TraversalOptions::SORTED)
+    }
+
+    pub fn compact_channels(self) -> Self {
+        self.with(TraversalOptions::COMPACT_CHANNELS)
+    }
+
+    pub fn parallel(self) -> Self {
+        self.with(TraversalOptions::PARALLEL)
+    }
+
+    pub fn hexdump(self) -> Self {
+        self.with(TraversalOptions::HEXDUMP)
+    }
+
+    pub fn hide_empty(self) -> Self {
+        self.with(TraversalOptions::HIDE_EMPTY)
+    }
+
+    pub fn build(self) -> TraversalOptions {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lays out an `AudioBufferList` declaring `declared` buffers, followed by one `AudioBuffer`
+    /// per entry in `channels`.
+    fn buffer_list_bytes(declared: u32, channels: &[u32]) -> Vec<u8> {
+        let ab_len = mem::size_of::<AudioBuffer>();
+        let abl_base_len = mem::size_of::<AudioBufferList>() - ab_len;
+        let mut data = vec![0u8; abl_base_len + channels.len() * ab_len];
+        data[..4].copy_from_slice(&declared.to_ne_bytes());
+        for (i, &channels) in channels.iter().enumerate() {
+            let buffer = AudioBuffer {
+                mNumberChannels: channels,
+                mDataByteSize: 0,
+                mData: ptr::null_mut(),
+            };
+            unsafe {
+                ptr::write_unaligned(
+                    data.as_mut_ptr().add(abl_base_len + i * ab_len) as *mut AudioBuffer,
+                    buffer,
+                )
+            };
+        }
+        data
+    }
+
+    #[test]
+    fn buffer_list_with_two_buffers() {
+        assert_eq!(parse_buffer_list(&buffer_list_bytes(2, &[1, 1])), [1, 1]);
+    }
+
+    #[test]
+    fn truncated_buffer_list() {
+        let mut data = buffer_list_bytes(2, &[2, 1]);
+        data.truncate(data.len() - mem::size_of::<AudioBuffer>() / 2);
+        assert_eq!(parse_buffer_list(&data), [2]);
+        assert_eq!(parse_buffer_list(&data[..2]), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn cstring_lossy_replaces_interior_nul() {
+        let s = cstring_lossy(b"Built-in\0Mic".to_vec());
+        assert_eq!(s.to_str().unwrap(), "Built-in\u{FFFD}Mic");
+        assert_eq!(cstring_lossy(b"Speakers".to_vec()).to_str().unwrap(), "Speakers");
+    }
+
+    #[test]
+    fn property_size_check() {
+        assert_eq!(
+            check_property_size::<f64>(4),
+            Err(kAudioHardwareBadPropertySizeError as OSStatus)
+        );
+        assert_eq!(
+            check_property_size::<u32>(0),
+            Err(kAudioHardwareBadPropertySizeError as OSStatus)
+        );
+        assert_eq!(check_property_size::<f64>(8), Ok(()));
+        assert_eq!(check_property_size::<AudioStreamBasicDescription>(40), Ok(()));
+    }
+
+    /// Lays out an `AudioChannelLayout` with `tag`, declaring `declared` descriptions, followed by
+    /// one `AudioChannelDescription` per entry in `labels`.
+    fn channel_layout_bytes(tag: u32, declared: u32, labels: &[u32]) -> Vec<u8> {
+        let acd_len = mem::size_of::<AudioChannelDescription>();
+        let acl_base_len = mem::size_of::<AudioChannelLayout>() - acd_len;
+        let mut data = vec![0u8; acl_base_len + labels.len() * acd_len];
+        data[..4].copy_from_slice(&tag.to_ne_bytes());
+        data[8..12].copy_from_slice(&declared.to_ne_bytes());
+        for (i, &label) in labels.iter().enumerate() {
+            let offset = acl_base_len + i * acd_len;
+            data[offset..offset + 4].copy_from_slice(&label.to_ne_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn channel_layout_from_tag() {
+        // Tag-based layouts declare no descriptions, but come with room for one.
+        let layout =
+            expand_channel_layout(channel_layout_bytes(kAudioChannelLayoutTag_Stereo, 0, &[0]))
+                .unwrap();
+        assert!(layout.mChannelDescriptions.is_empty());
+        assert_eq!(layout_channel_count(&layout), 2);
+    }
+
+    #[test]
+    fn channel_layout_from_descriptions() {
+        let data = channel_layout_bytes(
+            kAudioChannelLayoutTag_UseChannelDescriptions,
+            2,
+            &[kAudioChannelLabel_Left, kAudioChannelLabel_Right],
+        );
+        let layout = expand_channel_layout(data).unwrap();
+        let labels: Vec<u32> = layout
+            .mChannelDescriptions
+            .iter()
+            .map(|d| d.mChannelLabel)
+            .collect();
+        assert_eq!(labels, [kAudioChannelLabel_Left, kAudioChannelLabel_Right]);
+    }
+
+    #[test]
+    fn truncated_channel_layout() {
+        let data = channel_layout_bytes(
+            kAudioChannelLayoutTag_UseChannelDescriptions,
+            2,
+            &[kAudioChannelLabel_Left],
+        );
+        let layout = expand_channel_layout(data.clone()).unwrap();
+        assert_eq!(layout.mNumberChannelDescriptions, 2);
+        assert_eq!(layout.mChannelDescriptions.len(), 1);
+        assert_eq!(
+            expand_channel_layout(data[..8].to_vec()).unwrap_err(),
+            kAudioHardwareBadPropertySizeError as OSStatus
+        );
+    }
+
+    #[test]
+    fn formatted_prints_unquoted() {
+        assert_eq!(format!("{:?}", Formatted("50%".to_string())), "50%");
+    }
+
+    #[test]
+    fn transport_types() {
+        assert_eq!(transporttype_to_str(kAudioDeviceTransportTypeBuiltIn), "BuiltIn");
+        assert_eq!(transporttype_to_str(kAudioDeviceTransportTypeUSB), "USB");
+        assert_eq!(transporttype_to_str(kAudioDeviceTransportTypeBluetooth), "Bluetooth");
+        assert_eq!(transporttype_to_str(kAudioDeviceTransportTypeBluetoothLE), "BluetoothLE");
+        assert_eq!(transporttype_to_str(kAudioDeviceTransportTypeAggregate), "Aggregate");
+        assert_eq!(transporttype_to_str(u32::from_be_bytes(*b"none")), "Unexpected TransportType");
+    }
+
+    #[test]
+    fn terminal_types() {
+        assert_eq!(terminaltype_to_str(kAudioStreamTerminalTypeSpeaker), "Speaker");
+        assert_eq!(terminaltype_to_str(kAudioStreamTerminalTypeHeadphones), "Headphones");
+        assert_eq!(terminaltype_to_str(kAudioStreamTerminalTypeMicrophone), "Microphone");
+        assert_eq!(terminaltype_to_str(0xAB), "0x00AB");
     }
 }
@@ -0,0 +1,377 @@
+use coreaudio_sys::*;
+
+macro_rules! selectors {
+    ($($selector: ident),* $(,)?) => {
+        &[$((stringify!($selector), $selector)),*]
+    };
+}
+
+/// AudioObject, which every class derives from.
+pub const OBJECT: &[(&str, u32)] = selectors![
+    kAudioObjectPropertyBaseClass,
+    kAudioObjectPropertyClass,
+    kAudioObjectPropertyOwner,
+    kAudioObjectPropertyName,
+    kAudioObjectPropertyModelName,
+    kAudioObjectPropertyManufacturer,
+    kAudioObjectPropertyElementName,
+    kAudioObjectPropertyElementCategoryName,
+    kAudioObjectPropertyElementNumberName,
+    kAudioObjectPropertyOwnedObjects,
+    kAudioObjectPropertyIdentify,
+    kAudioObjectPropertySerialNumber,
+    kAudioObjectPropertyFirmwareVersion,
+    kAudioObjectPropertyControlList,
+];
+
+/// The system object.
+pub const SYSTEM: &[(&str, u32)] = selectors![
+    kAudioHardwarePropertyDevices,
+    kAudioHardwarePropertyDefaultInputDevice,
+    kAudioHardwarePropertyDefaultOutputDevice,
+    kAudioHardwarePropertyDefaultSystemOutputDevice,
+    kAudioHardwarePropertyTranslateUIDToDevice,
+    kAudioHardwarePropertyMixStereoToMono,
+    kAudioHardwarePropertyPlugInList,
+    kAudioHardwarePropertyTranslateBundleIDToPlugIn,
+    kAudioHardwarePropertyTransportManagerList,
+    kAudioHardwarePropertyTranslateBundleIDToTransportManager,
+    kAudioHardwarePropertyBoxList,
+    kAudioHardwarePropertyTranslateUIDToBox,
+    kAudioHardwarePropertyClockDeviceList,
+    kAudioHardwarePropertyTranslateUIDToClockDevice,
+    kAudioHardwarePropertyProcessIsMain,
+    kAudioHardwarePropertyIsInitingOrExiting,
+    kAudioHardwarePropertyUserIDChanged,
+    kAudioHardwarePropertyProcessInputMute,
+    kAudioHardwarePropertyProcessIsAudible,
+    kAudioHardwarePropertySleepingIsAllowed,
+    kAudioHardwarePropertyUnloadingIsAllowed,
+    kAudioHardwarePropertyHogModeIsAllowed,
+    kAudioHardwarePropertyUserSessionIsActiveOrHeadless,
+    kAudioHardwarePropertyServiceRestarted,
+    kAudioHardwarePropertyPowerHint,
+    kAudioHardwarePropertyProcessObjectList,
+    kAudioHardwarePropertyTranslatePIDToProcessObject,
+    kAudioHardwarePropertyTapList,
+    kAudioHardwarePropertyTranslateUIDToTap,
+];
+
+/// AudioTransportManager, in addition to AudioPlugIn.
+pub const TRANSPORT_MANAGER: &[(&str, u32)] = selectors![
+    kAudioTransportManagerPropertyEndPointList,
+    kAudioTransportManagerPropertyTranslateUIDToEndPoint,
+    kAudioTransportManagerPropertyTransportType,
+    kAudioTransportManagerCreateEndPointDevice,
+    kAudioTransportManagerDestroyEndPointDevice,
+];
+
+/// AudioPlugIn.
+pub const PLUGIN: &[(&str, u32)] = selectors![
+    kAudioPlugInPropertyBundleID,
+    kAudioPlugInPropertyDeviceList,
+    kAudioPlugInPropertyTranslateUIDToDevice,
+    kAudioPlugInPropertyBoxList,
+    kAudioPlugInPropertyTranslateUIDToBox,
+    kAudioPlugInPropertyClockDeviceList,
+    kAudioPlugInPropertyTranslateUIDToClockDevice,
+    kAudioPlugInCreateAggregateDevice,
+    kAudioPlugInDestroyAggregateDevice,
+];
+
+/// AudioBox.
+pub const BOX: &[(&str, u32)] = selectors![
+    kAudioBoxPropertyBoxUID,
+    kAudioBoxPropertyTransportType,
+    kAudioBoxPropertyHasAudio,
+    kAudioBoxPropertyHasVideo,
+    kAudioBoxPropertyHasMIDI,
+    kAudioBoxPropertyIsProtected,
+    kAudioBoxPropertyAcquired,
+    kAudioBoxPropertyAcquisitionFailed,
+    kAudioBoxPropertyDeviceList,
+    kAudioBoxPropertyClockDeviceList,
+];
+
+/// AudioClockDevice.
+pub const CLOCK_DEVICE: &[(&str, u32)] = selectors![
+    kAudioClockDevicePropertyDeviceUID,
+    kAudioClockDevicePropertyTransportType,
+    kAudioClockDevicePropertyClockDomain,
+    kAudioClockDevicePropertyDeviceIsAlive,
+    kAudioClockDevicePropertyDeviceIsRunning,
+    kAudioClockDevicePropertyLatency,
+    kAudioClockDevicePropertyControlList,
+    kAudioClockDevicePropertyNominalSampleRate,
+    kAudioClockDevicePropertyAvailableNominalSampleRates,
+];
+
+/// AudioEndPointDevice, in addition to AudioDevice.
+pub const ENDPOINT_DEVICE: &[(&str, u32)] = selectors![
+    kAudioEndPointDevicePropertyComposition,
+    kAudioEndPointDevicePropertyEndPointList,
+    kAudioEndPointDevicePropertyIsPrivate,
+];
+
+/// AudioAggregateDevice, in addition to AudioDevice.
+pub const AGGREGATE_DEVICE: &[(&str, u32)] = selectors![
+    kAudioAggregateDevicePropertyFullSubDeviceList,
+    kAudioAggregateDevicePropertyActiveSubDeviceList,
+    kAudioAggregateDevicePropertyComposition,
+    kAudioAggregateDevicePropertyMainSubDevice,
+    kAudioAggregateDevicePropertyClockDevice,
+    kAudioAggregateDevicePropertyTapList,
+    kAudioAggregateDevicePropertySubTapList,
+];
+
+/// AudioSubDevice, in addition to AudioDevice.
+pub const SUB_DEVICE: &[(&str, u32)] = selectors![
+    kAudioSubDevicePropertyExtraLatency,
+    kAudioSubDevicePropertyDriftCompensation,
+    kAudioSubDevicePropertyDriftCompensationQuality,
+];
+
+/// AudioDevice.
+pub const DEVICE: &[(&str, u32)] = selectors![
+    kAudioDevicePropertyConfigurationApplication,
+    kAudioDevicePropertyDeviceUID,
+    kAudioDevicePropertyModelUID,
+    kAudioDevicePropertyTransportType,
+    kAudioDevicePropertyRelatedDevices,
+    kAudioDevicePropertyClockDomain,
+    kAudioDevicePropertyDeviceIsAlive,
+    kAudioDevicePropertyDeviceIsRunning,
+    kAudioDevicePropertyDeviceCanBeDefaultDevice,
+    kAudioDevicePropertyDeviceCanBeDefaultSystemDevice,
+    kAudioDevicePropertyLatency,
+    kAudioDevicePropertyStreams,
+    kAudioDevicePropertySafetyOffset,
+    kAudioDevicePropertyNominalSampleRate,
+    kAudioDevicePropertyAvailableNominalSampleRates,
+    kAudioDevicePropertyIcon,
+    kAudioDevicePropertyIsHidden,
+    kAudioDevicePropertyPreferredChannelsForStereo,
+    kAudioDevicePropertyPreferredChannelLayout,
+    kAudioDevicePropertyPlugIn,
+    kAudioDevicePropertyDeviceHasChanged,
+    kAudioDevicePropertyDeviceIsRunningSomewhere,
+    kAudioDeviceProcessorOverload,
+    kAudioDevicePropertyIOStoppedAbnormally,
+    kAudioDevicePropertyHogMode,
+    kAudioDevicePropertyBufferFrameSize,
+    kAudioDevicePropertyBufferFrameSizeRange,
+    kAudioDevicePropertyUsesVariableBufferFrameSizes,
+    kAudioDevicePropertyIOCycleUsage,
+    kAudioDevicePropertyStreamConfiguration,
+    kAudioDevicePropertyIOProcStreamUsage,
+    kAudioDevicePropertyActualSampleRate,
+    kAudioDevicePropertyClockDevice,
+    kAudioDevicePropertyIOThreadOSWorkgroup,
+    kAudioDevicePropertyProcessMute,
+    kAudioDevicePropertyJackIsConnected,
+    kAudioDevicePropertyVolumeScalar,
+    kAudioDevicePropertyVolumeDecibels,
+    kAudioDevicePropertyVolumeRangeDecibels,
+    kAudioDevicePropertyVolumeScalarToDecibels,
+    kAudioDevicePropertyVolumeDecibelsToScalar,
+    kAudioDevicePropertyStereoPan,
+    kAudioDevicePropertyStereoPanChannels,
+    kAudioDevicePropertyMute,
+    kAudioDevicePropertySolo,
+    kAudioDevicePropertyPhantomPower,
+    kAudioDevicePropertyPhaseInvert,
+    kAudioDevicePropertyClipLight,
+    kAudioDevicePropertyTalkback,
+    kAudioDevicePropertyListenback,
+    kAudioDevicePropertyDataSource,
+    kAudioDevicePropertyDataSources,
+    kAudioDevicePropertyDataSourceNameForIDCFString,
+    kAudioDevicePropertyDataSourceKindForID,
+    kAudioDevicePropertyClockSource,
+    kAudioDevicePropertyClockSources,
+    kAudioDevicePropertyClockSourceNameForIDCFString,
+    kAudioDevicePropertyClockSourceKindForID,
+    kAudioDevicePropertyPlayThru,
+    kAudioDevicePropertyPlayThruSolo,
+    kAudioDevicePropertyPlayThruVolumeScalar,
+    kAudioDevicePropertyPlayThruVolumeDecibels,
+    kAudioDevicePropertyPlayThruVolumeRangeDecibels,
+    kAudioDevicePropertyPlayThruStereoPan,
+    kAudioDevicePropertyPlayThruStereoPanChannels,
+    kAudioDevicePropertyPlayThruDestination,
+    kAudioDevicePropertyPlayThruDestinations,
+    kAudioDevicePropertyChannelNominalLineLevel,
+    kAudioDevicePropertyChannelNominalLineLevels,
+    kAudioDevicePropertyHighPassFilterSetting,
+    kAudioDevicePropertyHighPassFilterSettings,
+    kAudioDevicePropertySubVolumeScalar,
+    kAudioDevicePropertySubVolumeDecibels,
+    kAudioDevicePropertySubVolumeRangeDecibels,
+    kAudioDevicePropertySubMute,
+    kAudioDevicePropertyVoiceActivityDetectionEnable,
+    kAudioDevicePropertyVoiceActivityDetectionState,
+];
+
+/// AudioStream.
+pub const STREAM: &[(&str, u32)] = selectors![
+    kAudioStreamPropertyIsActive,
+    kAudioStreamPropertyDirection,
+    kAudioStreamPropertyTerminalType,
+    kAudioStreamPropertyStartingChannel,
+    kAudioStreamPropertyLatency,
+    kAudioStreamPropertyVirtualFormat,
+    kAudioStreamPropertyAvailableVirtualFormats,
+    kAudioStreamPropertyPhysicalFormat,
+    kAudioStreamPropertyAvailablePhysicalFormats,
+];
+
+/// AudioControl, which every control class derives from.
+pub const CONTROL: &[(&str, u32)] =
+    selectors![kAudioControlPropertyScope, kAudioControlPropertyElement,];
+
+/// AudioSliderControl.
+pub const SLIDER_CONTROL: &[(&str, u32)] = selectors![
+    kAudioSliderControlPropertyValue,
+    kAudioSliderControlPropertyRange,
+];
+
+/// AudioLevelControl, the base class of e.g. volume controls.
+pub const LEVEL_CONTROL: &[(&str, u32)] = selectors![
+    kAudioLevelControlPropertyScalarValue,
+    kAudioLevelControlPropertyDecibelValue,
+    kAudioLevelControlPropertyDecibelRange,
+];
+
+/// AudioBooleanControl, the base class of e.g. mute controls.
+pub const BOOLEAN_CONTROL: &[(&str, u32)] = selectors![kAudioBooleanControlPropertyValue,];
+
+/// AudioSelectorControl, the base class of e.g. data source controls.
+pub const SELECTOR_CONTROL: &[(&str, u32)] = selectors![
+    kAudioSelectorControlPropertyCurrentItem,
+    kAudioSelectorControlPropertyAvailableItems,
+    kAudioSelectorControlPropertyItemName,
+    kAudioSelectorControlPropertyItemKind,
+];
+
+/// AudioStereoPanControl.
+pub const STEREO_PAN_CONTROL: &[(&str, u32)] = selectors![
+    kAudioStereoPanControlPropertyValue,
+    kAudioStereoPanControlPropertyPanningChannels,
+];
+
+/// AudioProcess.
+pub const PROCESS: &[(&str, u32)] = selectors![
+    kAudioProcessPropertyPID,
+    kAudioProcessPropertyBundleID,
+    kAudioProcessPropertyDevices,
+    kAudioProcessPropertyIsRunning,
+    kAudioProcessPropertyIsRunningInput,
+    kAudioProcessPropertyIsRunningOutput,
+];
+
+/// AudioSubTap.
+pub const SUB_TAP: &[(&str, u32)] = selectors![
+    kAudioSubTapPropertyExtraLatency,
+    kAudioSubTapPropertyDriftCompensation,
+    kAudioSubTapPropertyDriftCompensationQuality,
+];
+
+/// AudioTap.
+pub const TAP: &[(&str, u32)] = selectors![
+    kAudioTapPropertyUID,
+    kAudioTapPropertyDescription,
+    kAudioTapPropertyFormat,
+];
+
+/// Every group above.
+pub const ALL: &[&[(&str, u32)]] = &[
+    OBJECT,
+    SYSTEM,
+    TRANSPORT_MANAGER,
+    PLUGIN,
+    BOX,
+    CLOCK_DEVICE,
+    ENDPOINT_DEVICE,
+    AGGREGATE_DEVICE,
+    SUB_DEVICE,
+    DEVICE,
+    STREAM,
+    CONTROL,
+    SLIDER_CONTROL,
+    LEVEL_CONTROL,
+    BOOLEAN_CONTROL,
+    SELECTOR_CONTROL,
+    STEREO_PAN_CONTROL,
+    PROCESS,
+    SUB_TAP,
+    TAP,
+];
+
+/// The selectors declared by `class` itself, not by the classes it derives from. Empty for classes
+/// that declare none, like AudioVolumeControl, which only has AudioLevelControl's.
+#[allow(non_upper_case_globals)]
+pub fn known_selectors_for_class(class: AudioClassID) -> &'static [(&'static str, u32)] {
+    match class {
+        kAudioObjectClassID => OBJECT,
+        kAudioSystemObjectClassID => SYSTEM,
+        kAudioPlugInClassID => PLUGIN,
+        kAudioTransportManagerClassID => TRANSPORT_MANAGER,
+        kAudioBoxClassID => BOX,
+        kAudioDeviceClassID => DEVICE,
+        kAudioClockDeviceClassID => CLOCK_DEVICE,
+        kAudioEndPointDeviceClassID => ENDPOINT_DEVICE,
+        kAudioAggregateDeviceClassID => AGGREGATE_DEVICE,
+        kAudioSubDeviceClassID => SUB_DEVICE,
+        kAudioStreamClassID => STREAM,
+        kAudioControlClassID => CONTROL,
+        kAudioSliderControlClassID => SLIDER_CONTROL,
+        kAudioLevelControlClassID => LEVEL_CONTROL,
+        kAudioBooleanControlClassID => BOOLEAN_CONTROL,
+        kAudioSelectorControlClassID => SELECTOR_CONTROL,
+        kAudioStereoPanControlClassID => STEREO_PAN_CONTROL,
+        kAudioProcessClassID => PROCESS,
+        kAudioTapClassID => TAP,
+        kAudioSubTapClassID => SUB_TAP,
+        _ => &[],
+    }
+}
+
+/// The class `class` derives from, for the classes that have selector groups. Objects only
+/// report their immediate base class, so the rest of the chain is known statically.
+#[allow(non_upper_case_globals)]
+fn parent_class(class: AudioClassID) -> Option<AudioClassID> {
+    match class {
+        kAudioObjectClassID => None,
+        kAudioTransportManagerClassID => Some(kAudioPlugInClassID),
+        kAudioEndPointDeviceClassID | kAudioAggregateDeviceClassID | kAudioSubDeviceClassID => {
+            Some(kAudioDeviceClassID)
+        }
+        kAudioSliderControlClassID
+        | kAudioLevelControlClassID
+        | kAudioBooleanControlClassID
+        | kAudioSelectorControlClassID
+        | kAudioStereoPanControlClassID => Some(kAudioControlClassID),
+        _ => Some(kAudioObjectClassID),
+    }
+}
+
+/// The selectors that apply to an object of `class` with immediate base class `base_class`: those
+/// of the class and of every class it derives from, most generic first.
+pub fn known_selectors_for_classes(
+    base_class: AudioClassID,
+    class: AudioClassID,
+) -> Vec<(&'static str, u32)> {
+    let mut chain = vec![class];
+    let mut next = Some(base_class);
+    while let Some(c) = next {
+        if !chain.contains(&c) {
+            chain.push(c);
+        }
+        next = parent_class(c);
+    }
+    chain
+        .into_iter()
+        .rev()
+        .flat_map(|c| known_selectors_for_class(c).iter().copied())
+        .collect()
+}
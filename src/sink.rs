@@ -0,0 +1,307 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use debug_tree::default_tree;
+
+/// Receives what a traversal finds. By default the traversal prints through debug_tree; a sink
+/// set with `traverse_with_sink` receives the same branches and leaves instead.
+pub trait PropertySink {
+    fn branch(&mut self, label: &str);
+    fn end_branch(&mut self);
+    fn leaf(&mut self, text: &str);
+    /// A property read through `prop!`. Sinks that don't care about the distinction get it as a
+    /// "name: value" leaf.
+    fn property(&mut self, name: &str, value: &str) {
+        self.leaf(&format!("{}: {}", name, value));
+    }
+}
+
+/// Prints into debug_tree's default tree.
+struct TreeSink;
+
+impl PropertySink for TreeSink {
+    fn branch(&mut self, label: &str) {
+        default_tree().enter(label);
+    }
+
+    fn end_branch(&mut self) {
+        default_tree().exit();
+    }
+
+    fn leaf(&mut self, text: &str) {
+        default_tree().add_leaf(text);
+    }
+}
+
+/// What a branch of the traversal holds, in the order the traversal emitted it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NodeEntry {
+    Property(String, String),
+    /// A leaf that is not a property, like "(object 74 vanished during traversal)".
+    Note(String),
+    Child(AudioObjectNode),
+}
+
+/// A branch of the traversal, typically an AudioObject, with the properties read on it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AudioObjectNode {
+    pub label: String,
+    pub entries: Vec<NodeEntry>,
+}
+
+impl AudioObjectNode {
+    /// The label without annotations like " [default output]", which can change between passes
+    /// while the node stays the same.
+    fn key(&self) -> &str {
+        self.label.split(" [").next().unwrap_or(&self.label)
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = &AudioObjectNode> {
+        self.entries.iter().filter_map(|e| match e {
+            NodeEntry::Child(child) => Some(child),
+            _ => None,
+        })
+    }
+
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.entries.iter().find_map(|e| match e {
+            NodeEntry::Property(n, v) if n == name => Some(v.as_str()),
+            _ => None,
+        })
+    }
+
+    fn child(&self, key: &str) -> Option<&AudioObjectNode> {
+        self.children().find(|c| c.key() == key)
+    }
+
+    fn has_note(&self, note: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|e| matches!(e, NodeEntry::Note(n) if n == note))
+    }
+}
+
+/// Prints `node` like a traversal would, prefixing with "* " every branch and property that is
+/// new or changed since `previous`. Without `previous` nothing is marked.
+pub fn print_changes(node: &AudioObjectNode, previous: Option<&AudioObjectNode>) {
+    print_node_changes(&mut TreeSink, node, previous, previous.is_some());
+    default_tree().flush_print();
+}
+
+fn print_node_changes(
+    sink: &mut TreeSink,
+    node: &AudioObjectNode,
+    previous: Option<&AudioObjectNode>,
+    mark: bool,
+) {
+    let marker = |changed: bool| if mark && changed { "* " } else { "" };
+    sink.branch(&format!("{}{}", marker(previous.is_none()), node.label));
+    for entry in &node.entries {
+        match entry {
+            NodeEntry::Property(name, value) => {
+                let changed = previous.is_some_and(|p| p.property(name) != Some(value.as_str()));
+                sink.leaf(&format!("{}{}: {}", marker(changed), name, value));
+            }
+            NodeEntry::Note(note) => {
+                let changed = previous.is_some_and(|p| !p.has_note(note));
+                sink.leaf(&format!("{}{}", marker(changed), note));
+            }
+            NodeEntry::Child(child) => {
+                // Everything under a new branch is new, so it is only marked once, on the branch.
+                let previous_child = previous.and_then(|p| p.child(child.key()));
+                print_node_changes(sink, child, previous_child, mark && previous.is_some());
+            }
+        }
+    }
+    sink.end_branch();
+}
+
+/// Collects the traversal into an `AudioObjectNode` tree.
+pub struct NodeSink {
+    stack: Vec<AudioObjectNode>,
+}
+
+impl Default for NodeSink {
+    fn default() -> Self {
+        Self {
+            stack: vec![AudioObjectNode::default()],
+        }
+    }
+}
+
+impl NodeSink {
+    /// Returns the unlabeled root node, whose children are the traversed top-level branches.
+    pub fn into_root(mut self) -> AudioObjectNode {
+        while self.stack.len() > 1 {
+            self.end_branch();
+        }
+        self.stack.pop().unwrap()
+    }
+
+    fn top(&mut self) -> &mut AudioObjectNode {
+        self.stack.last_mut().unwrap()
+    }
+}
+
+impl PropertySink for NodeSink {
+    fn branch(&mut self, label: &str) {
+        self.stack.push(AudioObjectNode {
+            label: label.to_string(),
+            ..Default::default()
+        });
+    }
+
+    fn end_branch(&mut self) {
+        if self.stack.len() > 1 {
+            let node = self.stack.pop().unwrap();
+            self.top().entries.push(NodeEntry::Child(node));
+        }
+    }
+
+    fn leaf(&mut self, text: &str) {
+        self.top().entries.push(NodeEntry::Note(text.to_string()));
+    }
+
+    fn property(&mut self, name: &str, value: &str) {
+        self.top()
+            .entries
+            .push(NodeEntry::Property(name.to_string(), value.to_string()));
+    }
+}
+
+enum Event {
+    Branch(String),
+    EndBranch,
+    Leaf(String),
+    Property(String, String),
+}
+
+/// Records everything emitted into it, so a traversal done on another thread can be replayed into
+/// this thread's sink exactly as if it had happened here.
+#[derive(Default)]
+pub(crate) struct RecordingSink {
+    events: Vec<Event>,
+}
+
+impl RecordingSink {
+    /// Emits the recorded events, in order, into the current sink.
+    pub(crate) fn replay(self) {
+        for event in self.events {
+            match event {
+                Event::Branch(label) => emit(|sink| sink.branch(&label)),
+                Event::EndBranch => emit(|sink| sink.end_branch()),
+                Event::Leaf(text) => emit(|sink| sink.leaf(&text)),
+                Event::Property(name, value) => emit(|sink| sink.property(&name, &value)),
+            }
+        }
+    }
+}
+
+impl PropertySink for RecordingSink {
+    fn branch(&mut self, label: &str) {
+        self.events.push(Event::Branch(label.to_string()));
+    }
+
+    fn end_branch(&mut self) {
+        self.events.push(Event::EndBranch);
+    }
+
+    fn leaf(&mut self, text: &str) {
+        self.events.push(Event::Leaf(text.to_string()));
+    }
+
+    fn property(&mut self, name: &str, value: &str) {
+        self.events
+            .push(Event::Property(name.to_string(), value.to_string()));
+    }
+}
+
+thread_local! {
+    /// The sink of the ongoing `with_sink`, if any. Otherwise output goes to `TreeSink`.
+    static SINK: RefCell<Option<Rc<RefCell<dyn PropertySink>>>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn emit(f: impl FnOnce(&mut dyn PropertySink)) {
+    let sink = SINK.with(|sink| sink.borrow().clone());
+    match sink {
+        Some(sink) => f(&mut *sink.borrow_mut()),
+        None => f(&mut TreeSink),
+    }
+}
+
+/// Ends its branch when dropped, like debug_tree's scoped branches.
+pub(crate) struct SinkBranch;
+
+impl Drop for SinkBranch {
+    fn drop(&mut self) {
+        emit(|sink| sink.end_branch());
+    }
+}
+
+pub(crate) fn sink_branch(label: &str) -> SinkBranch {
+    emit(|sink| sink.branch(label));
+    SinkBranch
+}
+
+/// Reinstates the sink that was current before a `with_sink` when dropped, so a panicking `f`
+/// doesn't leave its sink installed.
+struct RestoreSink(Option<Rc<RefCell<dyn PropertySink>>>);
+
+impl Drop for RestoreSink {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        SINK.with(|s| *s.borrow_mut() = previous);
+    }
+}
+
+/// Runs `f` with everything it emits going to `sink`, then hands `sink` back.
+pub(crate) fn with_sink<S: PropertySink + 'static>(sink: S, f: impl FnOnce()) -> S {
+    let shared = Rc::new(RefCell::new(sink));
+    let current: Rc<RefCell<dyn PropertySink>> = shared.clone();
+    let restore = RestoreSink(SINK.with(|s| s.replace(Some(current))));
+    f();
+    drop(restore);
+    match Rc::try_unwrap(shared) {
+        Ok(sink) => sink.into_inner(),
+        Err(_) => unreachable!("the sink is only shared while f runs"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_sink_restores_after_panic() {
+        let result = std::panic::catch_unwind(|| {
+            with_sink(NodeSink::default(), || panic!("traversal failed"));
+        });
+        assert!(result.is_err());
+        assert!(SINK.with(|s| s.borrow().is_none()));
+    }
+
+    #[test]
+    fn node_sink_keeps_order() {
+        let mut sink = NodeSink::default();
+        sink.branch("Device");
+        sink.property("Name", "Speakers");
+        sink.branch("Stream");
+        sink.end_branch();
+        sink.leaf("(object 74 vanished during traversal)");
+        sink.property("Alive", "true");
+        let root = sink.into_root();
+        let device = root.children().next().unwrap();
+        assert_eq!(
+            device.entries,
+            [
+                NodeEntry::Property("Name".into(), "Speakers".into()),
+                NodeEntry::Child(AudioObjectNode {
+                    label: "Stream".into(),
+                    entries: vec![],
+                }),
+                NodeEntry::Note("(object 74 vanished during traversal)".into()),
+                NodeEntry::Property("Alive".into(), "true".into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::io::{self, Seek, SeekFrom, Write};
+
+const HEADER_LEN: u32 = 44;
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SampleFormat {
+    Float32,
+    Int16,
+}
+
+impl SampleFormat {
+    fn bytes_per_sample(self) -> u16 {
+        match self {
+            SampleFormat::Float32 => 4,
+            SampleFormat::Int16 => 2,
+        }
+    }
+
+    fn format_tag(self) -> u16 {
+        match self {
+            SampleFormat::Float32 => WAVE_FORMAT_IEEE_FLOAT,
+            SampleFormat::Int16 => WAVE_FORMAT_PCM,
+        }
+    }
+}
+
+/// Writes interleaved frames to a canonical 44-byte-header WAV file, starting at the writer's
+/// current position. The chunk sizes in the header are placeholders until `finalize` patches them.
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    format: SampleFormat,
+    channels: u16,
+    start: u64,
+    data_len: u32,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    pub fn new(mut writer: W, format: SampleFormat, channels: u16, rate: u32) -> io::Result<Self> {
+        assert!(channels > 0);
+        let start = writer.stream_position()?;
+        let block_align = channels * format.bytes_per_sample();
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(HEADER_LEN - 8).to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&format.format_tag().to_le_bytes())?;
+        writer.write_all(&channels.to_le_bytes())?;
+        writer.write_all(&rate.to_le_bytes())?;
+        writer.write_all(&(rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&(format.bytes_per_sample() * 8).to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            format,
+            channels,
+            start,
+            data_len: 0,
+        })
+    }
+
+    /// Writes interleaved samples in [-1, 1], converting them to the file's sample format.
+    pub fn write_frames(&mut self, samples: &[f32]) -> io::Result<()> {
+        debug_assert_eq!(samples.len() % self.channels as usize, 0);
+        // The RIFF chunk size covers the rest of the header too, so it is the first to overflow.
+        let data_len = u32::try_from(samples.len() * self.format.bytes_per_sample() as usize)
+            .ok()
+            .and_then(|len| self.data_len.checked_add(len))
+            .filter(|&len| len <= u32::MAX - (HEADER_LEN - 8))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "WAV data exceeds 4 GiB"))?;
+        for &sample in samples {
+            match self.format {
+                SampleFormat::Float32 => self.writer.write_all(&sample.to_le_bytes())?,
+                SampleFormat::Int16 => {
+                    let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+                    self.writer.write_all(&sample.to_le_bytes())?
+                }
+            }
+        }
+        self.data_len = data_len;
+        Ok(())
+    }
+
+    /// Patches the RIFF and data chunk sizes into the header and returns the inner writer.
+    pub fn finalize(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        self.writer.seek(SeekFrom::Start(self.start + 4))?;
+        self.writer
+            .write_all(&(HEADER_LEN - 8 + self.data_len).to_le_bytes())?;
+        self.writer
+            .seek(SeekFrom::Start(self.start + HEADER_LEN as u64 - 4))?;
+        self.writer.write_all(&self.data_len.to_le_bytes())?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn u32_at(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn header_and_chunk_sizes() {
+        let mut writer =
+            WavWriter::new(Cursor::new(Vec::new()), SampleFormat::Int16, 2, 48000).unwrap();
+        writer.write_frames(&[0.0; 2 * 10]).unwrap();
+        let data = writer.finalize().unwrap().into_inner();
+        assert_eq!(data.len(), HEADER_LEN as usize + 2 * 10 * 2);
+        assert_eq!(&data[..4], b"RIFF");
+        assert_eq!(u32_at(&data, 4), data.len() as u32 - 8);
+        assert_eq!(&data[36..40], b"data");
+        assert_eq!(u32_at(&data, 40), 2 * 10 * 2);
+    }
+
+    #[test]
+    fn header_after_existing_data() {
+        let mut cursor = Cursor::new(Vec::new());
+        cursor.write_all(b"junk").unwrap();
+        let mut writer = WavWriter::new(cursor, SampleFormat::Float32, 1, 44100).unwrap();
+        writer.write_frames(&[0.5; 3]).unwrap();
+        let data = writer.finalize().unwrap().into_inner();
+        assert_eq!(&data[..4], b"junk");
+        let data = &data[4..];
+        assert_eq!(u32_at(data, 4), HEADER_LEN - 8 + 3 * 4);
+        assert_eq!(u32_at(data, 40), 3 * 4);
+    }
+
+    #[test]
+    fn data_len_overflow() {
+        let mut writer =
+            WavWriter::new(Cursor::new(Vec::new()), SampleFormat::Int16, 1, 48000).unwrap();
+        writer.data_len = u32::MAX - (HEADER_LEN - 8) - 1;
+        assert!(writer.write_frames(&[0.0]).is_err());
+        assert_eq!(writer.data_len, u32::MAX - (HEADER_LEN - 8) - 1);
+    }
+}
@@ -0,0 +1,41 @@
+//! Traverses the live system, so this only runs on macOS. Runners without audio hardware still
+//! have the system object and its plugins.
+#![cfg(target_os = "macos")]
+
+use coreaudio_sys::{kAudioHardwarePropertyDevices, kAudioObjectSystemObject, AudioObjectID};
+use cubeb_coreaudio_samples::sink::{AudioObjectNode, NodeEntry};
+use cubeb_coreaudio_samples::{get_list_property, traverse_to_node, TraversalOptions};
+
+/// Whether `node` or any node under it has an Input- or Output-scoped property.
+fn has_scoped_property(node: &AudioObjectNode) -> bool {
+    node.entries.iter().any(|e| match e {
+        NodeEntry::Property(name, _) => name.starts_with("Input ") || name.starts_with("Output "),
+        NodeEntry::Child(child) => has_scoped_property(child),
+        NodeEntry::Note(_) => false,
+    })
+}
+
+#[test]
+fn full_traversal() {
+    // A panic in any object fails the test instead of becoming a leaf.
+    let system =
+        traverse_to_node(TraversalOptions::INCLUDE_ALL | TraversalOptions::PROPAGATE_PANICS);
+    assert!(
+        system
+            .label
+            .starts_with(&format!("AudioObjectID: {}", kAudioObjectSystemObject)),
+        "{}",
+        system.label
+    );
+    assert!(system.entries.iter().any(|e| matches!(
+        e,
+        NodeEntry::Note(note) if note == "Class (Known): \"AudioSystemObject\""
+    )));
+    assert!(system.children().count() > 0);
+    let devices =
+        get_list_property::<AudioObjectID>(kAudioObjectSystemObject, kAudioHardwarePropertyDevices)
+            .unwrap();
+    if !devices.is_empty() {
+        assert!(has_scoped_property(&system));
+    }
+}